@@ -0,0 +1,41 @@
+use std::path::Path;
+
+/// Write mono samples to a 16-bit PCM WAV file
+pub fn write_wav(path: &Path, samples: &[f32], sample_rate: u32) -> Result<(), Box<dyn std::error::Error>> {
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+
+    let mut writer = hound::WavWriter::create(path, spec)?;
+    for &sample in samples {
+        let value = (sample.clamp(-1.0, 1.0) * i16::MAX as f32).round() as i16;
+        writer.write_sample(value)?;
+    }
+    writer.finalize()?;
+
+    tracing::info!("Saved {} samples ({} Hz) to {}", samples.len(), sample_rate, path.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_wav_roundtrip() {
+        let path = std::env::temp_dir().join(format!("archive-test-{}.wav", std::process::id()));
+        let samples = [0.0, 0.5, -0.5, 1.5, -1.0];
+        write_wav(&path, &samples, 16000).unwrap();
+
+        let mut reader = hound::WavReader::open(&path).unwrap();
+        assert_eq!(reader.spec().sample_rate, 16000);
+        assert_eq!(reader.spec().channels, 1);
+        let read: Vec<i16> = reader.samples::<i16>().map(|s| s.unwrap()).collect();
+        assert_eq!(read, vec![0, 16384, -16384, i16::MAX, -i16::MAX]);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
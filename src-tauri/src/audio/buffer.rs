@@ -0,0 +1,270 @@
+use std::sync::Mutex;
+
+/// Samples per chunk (~1.4 s at 48 kHz). Chunks are never reallocated, so a push
+/// never copies the recording so far.
+const CHUNK_SAMPLES: usize = 64 * 1024;
+
+/// Empty chunks kept ready for the audio callback by `reserve`
+const SPARE_CHUNKS: usize = 2;
+
+struct Chunks {
+    chunks: Vec<Vec<f32>>,
+    /// Pre-allocated empty chunks, taken by `push` instead of allocating
+    spare: Vec<Vec<f32>>,
+    len: usize,
+    max_samples: usize,
+    overflowed: bool,
+}
+
+impl Chunks {
+    fn new(max_samples: usize, spare: Vec<Vec<f32>>) -> Self {
+        Self {
+            chunks: Vec::new(),
+            spare,
+            len: 0,
+            max_samples,
+            overflowed: false,
+        }
+    }
+}
+
+/// Samples taken out of a `RecordingBuffer`
+pub struct RecordedAudio {
+    pub samples: Vec<f32>,
+    /// Whether samples were dropped because the length limit was reached
+    pub overflowed: bool,
+}
+
+/// Bounded recording buffer stored as a list of fixed-size chunks.
+///
+/// The audio callback only holds the lock while copying one callback's worth of
+/// samples into chunks allocated in advance by `reserve`; `take` swaps the chunk
+/// list out in O(1) and concatenates it after the lock is released, so stopping
+/// a long recording never stalls the callback.
+pub struct RecordingBuffer {
+    inner: Mutex<Chunks>,
+}
+
+impl RecordingBuffer {
+    pub fn new(max_samples: usize) -> Self {
+        Self {
+            inner: Mutex::new(Chunks::new(max_samples, Vec::new())),
+        }
+    }
+
+    /// Allocate spare chunks for the audio callback. Call it regularly from a
+    /// thread other than the callback while recording.
+    pub fn reserve(&self) {
+        let missing = {
+            let mut inner = self.inner.lock().unwrap();
+            // Room for the chunk list itself, so `push` does not grow it either
+            inner.chunks.reserve(SPARE_CHUNKS);
+            SPARE_CHUNKS.saturating_sub(inner.spare.len())
+        };
+        if missing == 0 {
+            return;
+        }
+        // Allocated without the lock, so the callback is not kept waiting
+        let chunks: Vec<Vec<f32>> = (0..missing).map(|_| Vec::with_capacity(CHUNK_SAMPLES)).collect();
+        self.inner.lock().unwrap().spare.extend(chunks);
+    }
+
+    /// Append samples (called from the audio callback).
+    /// Samples beyond the length limit are dropped and the buffer is marked as overflowed.
+    pub fn push(&self, mut samples: &[f32]) {
+        let mut inner = self.inner.lock().unwrap();
+
+        let remaining = inner.max_samples - inner.len;
+        if samples.len() > remaining {
+            samples = &samples[..remaining];
+            inner.overflowed = true;
+        }
+        inner.len += samples.len();
+
+        while !samples.is_empty() {
+            let needs_chunk = inner
+                .chunks
+                .last()
+                .is_none_or(|chunk| chunk.len() == CHUNK_SAMPLES);
+            if needs_chunk {
+                // Only allocates when `reserve` has fallen behind
+                let chunk = inner.spare.pop().unwrap_or_else(|| Vec::with_capacity(CHUNK_SAMPLES));
+                inner.chunks.push(chunk);
+            }
+            let chunk = inner.chunks.last_mut().unwrap();
+            let n = samples.len().min(CHUNK_SAMPLES - chunk.len());
+            chunk.extend_from_slice(&samples[..n]);
+            samples = &samples[n..];
+        }
+    }
+
+    /// Clear the buffer and set a new length limit (spare chunks are kept)
+    pub fn reset(&self, max_samples: usize) {
+        let old = {
+            let mut inner = self.inner.lock().unwrap();
+            let spare = std::mem::take(&mut inner.spare);
+            std::mem::replace(&mut *inner, Chunks::new(max_samples, spare))
+        };
+        // The recorded chunks are freed here, after the lock is released
+        drop(old);
+        self.reserve();
+    }
+
+    /// Number of samples recorded so far
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().len
+    }
+
+    /// Copy the samples recorded after `offset` (for readers that follow a live recording)
+    pub fn snapshot_since(&self, offset: usize) -> Vec<f32> {
+        let inner = self.inner.lock().unwrap();
+        let mut out = Vec::with_capacity(inner.len.saturating_sub(offset));
+        let first_chunk = offset / CHUNK_SAMPLES;
+        for (i, chunk) in inner.chunks.iter().enumerate().skip(first_chunk) {
+            let start = if i == first_chunk { offset % CHUNK_SAMPLES } else { 0 };
+            out.extend_from_slice(&chunk[start.min(chunk.len())..]);
+        }
+        out
+    }
+
+    /// Take ownership of the recorded samples, leaving the buffer empty
+    /// (the length limit and spare chunks are kept)
+    pub fn take(&self) -> RecordedAudio {
+        let chunks = {
+            let mut inner = self.inner.lock().unwrap();
+            let max_samples = inner.max_samples;
+            let spare = std::mem::take(&mut inner.spare);
+            std::mem::replace(&mut *inner, Chunks::new(max_samples, spare))
+        };
+
+        let mut samples = Vec::with_capacity(chunks.len);
+        for chunk in chunks.chunks {
+            samples.extend_from_slice(&chunk);
+        }
+        RecordedAudio {
+            samples,
+            overflowed: chunks.overflowed,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_push_and_take_across_chunks() {
+        let buffer = RecordingBuffer::new(usize::MAX);
+        let samples: Vec<f32> = (0..CHUNK_SAMPLES * 2 + 100).map(|i| i as f32).collect();
+        for block in samples.chunks(1000) {
+            buffer.push(block);
+        }
+        assert_eq!(buffer.len(), samples.len());
+
+        let recorded = buffer.take();
+        assert_eq!(recorded.samples, samples);
+        assert!(!recorded.overflowed);
+        assert_eq!(buffer.len(), 0);
+    }
+
+    #[test]
+    fn test_length_limit() {
+        let buffer = RecordingBuffer::new(10);
+        buffer.push(&[1.0; 8]);
+        buffer.push(&[2.0; 8]);
+
+        let recorded = buffer.take();
+        assert_eq!(recorded.samples.len(), 10);
+        assert_eq!(recorded.samples[9], 2.0);
+        assert!(recorded.overflowed);
+
+        // The limit survives `take`, the overflow flag does not
+        buffer.push(&[3.0; 4]);
+        let recorded = buffer.take();
+        assert_eq!(recorded.samples.len(), 4);
+        assert!(!recorded.overflowed);
+    }
+
+    #[test]
+    fn test_push_uses_reserved_chunks() {
+        let buffer = RecordingBuffer::new(usize::MAX);
+        buffer.reserve();
+        let spare_ptr = buffer.inner.lock().unwrap().spare.last().unwrap().as_ptr();
+
+        buffer.push(&[1.0; 10]);
+        {
+            let inner = buffer.inner.lock().unwrap();
+            assert_eq!(inner.chunks[0].as_ptr(), spare_ptr);
+            assert_eq!(inner.spare.len(), SPARE_CHUNKS - 1);
+        }
+
+        // Spare chunks survive `take` and are topped up again
+        buffer.take();
+        buffer.reserve();
+        assert_eq!(buffer.inner.lock().unwrap().spare.len(), SPARE_CHUNKS);
+    }
+
+    #[test]
+    fn test_snapshot_since() {
+        let buffer = RecordingBuffer::new(usize::MAX);
+        let samples: Vec<f32> = (0..CHUNK_SAMPLES + 500).map(|i| i as f32).collect();
+        buffer.push(&samples);
+
+        assert_eq!(buffer.snapshot_since(0), samples);
+        assert_eq!(buffer.snapshot_since(CHUNK_SAMPLES - 10), samples[CHUNK_SAMPLES - 10..]);
+        assert_eq!(buffer.snapshot_since(CHUNK_SAMPLES), samples[CHUNK_SAMPLES..]);
+        assert!(buffer.snapshot_since(samples.len()).is_empty());
+        assert!(buffer.snapshot_since(samples.len() + 5).is_empty());
+    }
+
+    /// Simulates a 5 minute 48 kHz recording in 10 ms callbacks and reports the
+    /// callback (push) latency, the stop (take) time and the memory held, next to
+    /// the previous single `Vec` + clone approach.
+    /// Run with `cargo test --release bench_long_recording -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn bench_long_recording() {
+        const SAMPLE_RATE: usize = 48_000;
+        const SECONDS: usize = 300;
+        const BLOCK: usize = SAMPLE_RATE / 100;
+        let block = vec![0.25f32; BLOCK];
+        let blocks = SAMPLE_RATE * SECONDS / BLOCK;
+
+        let buffer = RecordingBuffer::new(SAMPLE_RATE * SECONDS);
+        let mut max_push = Duration::ZERO;
+        let start = Instant::now();
+        for _ in 0..blocks {
+            let t = Instant::now();
+            buffer.push(&block);
+            max_push = max_push.max(t.elapsed());
+        }
+        let avg_push = start.elapsed() / blocks as u32;
+        let chunk_bytes = {
+            let inner = buffer.inner.lock().unwrap();
+            inner.chunks.iter().map(|c| c.capacity()).sum::<usize>() * 4
+        };
+        let t = Instant::now();
+        let recorded = buffer.take();
+        let take_time = t.elapsed();
+        assert_eq!(recorded.samples.len(), SAMPLE_RATE * SECONDS);
+
+        let vec_buffer = Mutex::new(Vec::new());
+        let mut vec_max_push = Duration::ZERO;
+        for _ in 0..blocks {
+            let t = Instant::now();
+            vec_buffer.lock().unwrap().extend_from_slice(&block);
+            vec_max_push = vec_max_push.max(t.elapsed());
+        }
+        let vec_bytes = vec_buffer.lock().unwrap().capacity() * 4;
+        let t = Instant::now();
+        let cloned = vec_buffer.lock().unwrap().clone();
+        let clone_lock_time = t.elapsed();
+        assert_eq!(cloned.len(), SAMPLE_RATE * SECONDS);
+
+        println!("chunked: avg push {:?}, max push {:?}, take {:?} (lock held O(1)), {} MiB",
+            avg_push, max_push, take_time, chunk_bytes >> 20);
+        println!("vec+clone: max push {:?}, clone under lock {:?}, {} MiB (+{} MiB clone)",
+            vec_max_push, clone_lock_time, vec_bytes >> 20, (cloned.capacity() * 4) >> 20);
+    }
+}
@@ -1,97 +1,467 @@
-use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
-use cpal::{Device, Sample, Stream, StreamConfig};
+use cpal::traits::{DeviceTrait, StreamTrait};
+use cpal::{Device, Stream, StreamConfig, SupportedStreamConfig};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 
+use super::buffer::RecordingBuffer;
+use super::device::{find_config_with_sample_rate, input_devices_by_priority, open_input_device};
+use super::level::LevelMeter;
+use super::mix::ChannelMixer;
+use crate::config::ChannelMix;
+
+/// Callback invoked from the audio thread when the capture device disappears
+pub type DeviceLostCallback = Arc<dyn Fn() + Send + Sync>;
+
+/// How often spare recording chunks are topped up while recording. One chunk
+/// lasts well over this even at high sample rates.
+const CHUNK_RESERVE_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+/// Frames per callback the mono buffer is sized for up front. Streams with the default
+/// buffer size deliver far fewer frames per callback on common backends.
+const MAX_CALLBACK_FRAMES: usize = 8192;
+
+/// Bounded ring buffer holding the most recent samples captured while not recording
+struct PrerollBuffer {
+    samples: VecDeque<f32>,
+    capacity: usize,
+}
+
+impl PrerollBuffer {
+    fn new(capacity: usize) -> Self {
+        Self {
+            samples: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Change the capacity, keeping the most recent samples
+    fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        let excess = self.samples.len().saturating_sub(capacity);
+        self.samples.drain(..excess);
+        self.samples.reserve(capacity.saturating_sub(self.samples.len()));
+    }
+
+    /// Append samples, discarding the oldest ones beyond capacity
+    fn push(&mut self, samples: &[f32]) {
+        if samples.len() >= self.capacity {
+            self.samples.clear();
+            self.samples.extend(&samples[samples.len() - self.capacity..]);
+            return;
+        }
+        let overflow = (self.samples.len() + samples.len()).saturating_sub(self.capacity);
+        self.samples.drain(..overflow);
+        self.samples.extend(samples);
+    }
+
+    /// Move all buffered samples (oldest first) to the end of `out`
+    fn drain_into(&mut self, out: &RecordingBuffer) {
+        let (front, back) = self.samples.as_slices();
+        out.push(front);
+        out.push(back);
+        self.samples.clear();
+    }
+}
+
+/// Buffers written by the audio callback
+#[derive(Clone)]
+struct SharedBuffers {
+    samples: Arc<RecordingBuffer>,
+    preroll: Arc<Mutex<PrerollBuffer>>,
+    /// Whether the callback writes to `samples` (recording) or to `preroll` (warm idle)
+    recording: Arc<AtomicBool>,
+}
+
+impl SharedBuffers {
+    /// Route a block of mono samples from the audio callback
+    fn write(&self, mono: &[f32]) {
+        if self.recording.load(Ordering::Acquire) {
+            self.samples.push(mono);
+            return;
+        }
+
+        // Re-check under the pre-roll lock: the flag is only flipped while holding it
+        let mut preroll = self.preroll.lock().unwrap();
+        if self.recording.load(Ordering::Acquire) {
+            self.samples.push(mono);
+        } else {
+            preroll.push(mono);
+        }
+    }
+}
+
 pub struct AudioCapture {
-    buffer: Arc<Mutex<Vec<f32>>>,
+    buffers: SharedBuffers,
     sample_rate: u32,
+    /// Input device name from settings (None = OS default)
+    preferred_device: Option<String>,
+    /// Name of the device used for the current/last recording
+    device_name: Option<String>,
+    /// Set when the preferred device was not found on the last open
+    missing_device: Option<String>,
+    /// The running input stream (dropping it stops capture)
+    stream: Option<Stream>,
+    /// Cleared by the stream error callback when the device disappears
+    stream_alive: Arc<AtomicBool>,
+    /// Preferred device at the time the stream was opened
+    stream_preferred_device: Option<String>,
+    /// How device channels are mixed down to mono
+    channel_mix: ChannelMix,
+    /// Channel mix the current stream was opened with
+    stream_channel_mix: ChannelMix,
+    on_device_lost: Option<DeviceLostCallback>,
+    /// Input level accumulated by the audio callback
+    level_meter: Arc<LevelMeter>,
+    /// Keep the stream open between recordings and fill the pre-roll buffer
+    always_warm: bool,
+    /// Audio from before the shortcut press prepended to a recording (warm mode only)
+    preroll_ms: u32,
+    /// Upper bound for the recording buffer (0 = unlimited)
+    max_recording_seconds: u32,
+    /// Stop flag of the thread allocating recording chunks, so the callback never does
+    chunk_allocator: Option<Arc<AtomicBool>>,
 }
 
 impl AudioCapture {
-    pub fn new() -> Result<Self, Box<dyn std::error::Error>> {
-        let host = cpal::default_host();
-        let device = host
-            .default_input_device()
-            .ok_or("No input device available")?;
+    pub fn new(preferred_device: Option<String>) -> Result<Self, Box<dyn std::error::Error>> {
+        let selected = open_input_device(preferred_device.as_deref())?;
 
-        let config = device.default_input_config()?;
+        let config = selected.device.default_input_config()?;
         let sample_rate = config.sample_rate().0;
 
-        tracing::info!("Default input config: {:?}", config);
+        tracing::info!("Input device: {}, config: {:?}", selected.name, config);
 
         Ok(Self {
-            buffer: Arc::new(Mutex::new(Vec::new())),
+            buffers: SharedBuffers {
+                samples: Arc::new(RecordingBuffer::new(0)),
+                preroll: Arc::new(Mutex::new(PrerollBuffer::new(0))),
+                recording: Arc::new(AtomicBool::new(false)),
+            },
             sample_rate,
+            preferred_device,
+            device_name: Some(selected.name),
+            missing_device: None,
+            stream: None,
+            stream_alive: Arc::new(AtomicBool::new(false)),
+            stream_preferred_device: None,
+            channel_mix: ChannelMix::default(),
+            stream_channel_mix: ChannelMix::default(),
+            on_device_lost: None,
+            level_meter: Arc::new(LevelMeter::new()),
+            always_warm: false,
+            preroll_ms: 0,
+            max_recording_seconds: 0,
+            chunk_allocator: None,
         })
     }
 
-    pub fn start_recording(&self) -> Result<Box<Stream>, Box<dyn std::error::Error>> {
-        let host = cpal::default_host();
-        let device = host
-            .default_input_device()
-            .ok_or("No input device available")?;
+    /// Change the preferred input device (takes effect on the next recording)
+    pub fn set_preferred_device(&mut self, device: Option<String>) {
+        self.preferred_device = device;
+    }
+
+    /// Change the channel mix (a warm stream is reopened on the next start)
+    pub fn set_channel_mix(&mut self, mix: ChannelMix) {
+        self.channel_mix = mix;
+    }
 
-        let config = device.default_input_config()?;
-        let stream_config: StreamConfig = config.config();
+    /// Limit the length of a recording; samples beyond it are dropped
+    pub fn set_max_recording_seconds(&mut self, seconds: u32) {
+        self.max_recording_seconds = seconds;
+    }
 
-        let buffer = Arc::clone(&self.buffer);
+    /// Configure always-warm capture. When enabled the stream stays open between
+    /// recordings and the last `preroll_ms` of audio is prepended to the next recording.
+    pub fn set_warm_mode(&mut self, always_warm: bool, preroll_ms: u32) {
+        self.always_warm = always_warm;
+        self.preroll_ms = preroll_ms;
+        let capacity = self.preroll_capacity();
+        self.buffers.preroll.lock().unwrap().set_capacity(capacity);
 
-        // Clear previous buffer
-        buffer.lock().unwrap().clear();
+        if !always_warm && !self.buffers.recording.load(Ordering::Acquire) {
+            self.close_stream();
+        }
+    }
 
-        let err_fn = |err| {
-            tracing::error!("Audio stream error: {}", err);
+    /// Open the input stream without recording so the pre-roll buffer starts filling.
+    /// Does nothing unless warm mode is enabled or when a usable stream is already open.
+    pub fn start_warm(&mut self, on_device_lost: DeviceLostCallback) -> Result<(), Box<dyn std::error::Error>> {
+        if !self.always_warm || self.buffers.recording.load(Ordering::Acquire) || self.stream_is_usable() {
+            return Ok(());
+        }
+
+        self.open_selected_device(on_device_lost)?;
+        tracing::info!("Warm capture started on {:?} ({} Hz)", self.device_name, self.sample_rate);
+        Ok(())
+    }
+
+    /// Start recording. In warm mode the running stream is reused and the pre-roll
+    /// is moved into the recording buffer; otherwise a new stream is opened.
+    pub fn start_recording(
+        &mut self,
+        on_device_lost: DeviceLostCallback,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let reuse_warm_stream = self.always_warm && self.stream_is_usable();
+        if !reuse_warm_stream {
+            self.open_selected_device(on_device_lost)?;
+        }
+
+        // Clear previous level readings
+        self.level_meter.take();
+
+        // Move the pre-roll into the recording buffer and switch the callback over atomically
+        let preroll_samples = {
+            let mut preroll = self.buffers.preroll.lock().unwrap();
+            self.buffers.samples.reset(self.max_samples());
+            preroll.drain_into(&self.buffers.samples);
+            self.buffers.recording.store(true, Ordering::Release);
+            self.buffers.samples.len()
         };
+        self.start_chunk_allocator();
 
-        let stream = match config.sample_format() {
-            cpal::SampleFormat::F32 => self.build_stream::<f32>(&device, &stream_config, buffer, err_fn)?,
-            cpal::SampleFormat::I16 => self.build_stream::<i16>(&device, &stream_config, buffer, err_fn)?,
-            cpal::SampleFormat::U16 => self.build_stream::<u16>(&device, &stream_config, buffer, err_fn)?,
-            sample_format => {
-                return Err(format!("Unsupported sample format: {}", sample_format).into())
+        tracing::info!(
+            "Recording started on {:?} ({} Hz, warm: {}, pre-roll: {} samples)",
+            self.device_name,
+            self.sample_rate,
+            reuse_warm_stream,
+            preroll_samples
+        );
+
+        Ok(())
+    }
+
+    /// Re-open capture on the next available device after the current one was lost.
+    /// Samples keep being appended to the same buffer, so only devices that can
+    /// capture at the current sample rate are considered.
+    /// Returns the name of the device that was opened.
+    pub fn reconnect(&mut self) -> Result<String, Box<dyn std::error::Error>> {
+        // Drop the dead stream first so its error callback can no longer fire
+        self.stream = None;
+
+        for (name, device) in input_devices_by_priority(self.preferred_device.as_deref()) {
+            let Some(config) = find_config_with_sample_rate(&device, self.sample_rate) else {
+                tracing::info!("Skipping {}: {} Hz not supported", name, self.sample_rate);
+                continue;
+            };
+
+            match self.open_stream(&device, config) {
+                Ok(()) => {
+                    tracing::info!("Recording resumed on {} ({} Hz)", name, self.sample_rate);
+                    self.device_name = Some(name.clone());
+                    return Ok(name);
+                }
+                Err(e) => tracing::warn!("Failed to open {}: {}", name, e),
             }
-        };
+        }
 
-        stream.play()?;
-        tracing::info!("Recording started");
+        Err(format!("No input device available at {} Hz", self.sample_rate).into())
+    }
+
+    /// Stop recording and take the recorded samples out of the buffer.
+    /// In warm mode the stream keeps running and refills the pre-roll buffer.
+    pub fn stop_recording(&mut self) -> Vec<f32> {
+        {
+            let _preroll = self.buffers.preroll.lock().unwrap();
+            self.buffers.recording.store(false, Ordering::Release);
+        }
+        self.stop_chunk_allocator();
+        if !self.always_warm {
+            self.close_stream();
+        }
+        let recorded = self.buffers.samples.take();
+        if recorded.overflowed {
+            tracing::warn!(
+                "Recording exceeded {} s; later samples were dropped",
+                self.max_recording_seconds
+            );
+        }
+        recorded.samples
+    }
 
-        Ok(Box::new(stream))
+    /// Copy the samples recorded after `offset` without taking them out of the buffer
+    pub fn recorded_since(&self, offset: usize) -> Vec<f32> {
+        self.buffers.samples.snapshot_since(offset)
     }
 
-    pub fn stop_recording(&self) -> Vec<f32> {
-        let buffer = self.buffer.lock().unwrap();
-        buffer.clone()
+    /// Take the samples recorded so far while the recording continues
+    /// (hands-free listening reads the stream in small pieces)
+    pub fn take_recorded(&self) -> Vec<f32> {
+        self.buffers.samples.take().samples
     }
 
     pub fn get_sample_rate(&self) -> u32 {
         self.sample_rate
     }
 
+    /// Name of the device used for the current/last recording
+    pub fn device_name(&self) -> Option<&str> {
+        self.device_name.as_deref()
+    }
+
+    /// The saved device name if it was missing and the default device was used instead
+    pub fn missing_device(&self) -> Option<&str> {
+        self.missing_device.as_deref()
+    }
+
+    /// Shared meter for reading live input levels while recording
+    pub fn level_meter(&self) -> Arc<LevelMeter> {
+        Arc::clone(&self.level_meter)
+    }
+
+    /// Keep spare chunks in the recording buffer until the recording stops
+    fn start_chunk_allocator(&mut self) {
+        self.stop_chunk_allocator();
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_clone = stop.clone();
+        let samples = self.buffers.samples.clone();
+        std::thread::spawn(move || {
+            while !stop_clone.load(Ordering::Acquire) {
+                samples.reserve();
+                std::thread::sleep(CHUNK_RESERVE_INTERVAL);
+            }
+        });
+        self.chunk_allocator = Some(stop);
+    }
+
+    fn stop_chunk_allocator(&mut self) {
+        if let Some(stop) = self.chunk_allocator.take() {
+            stop.store(true, Ordering::Release);
+        }
+    }
+
+    /// Recording buffer limit in samples, including room for the pre-roll
+    fn max_samples(&self) -> usize {
+        if self.max_recording_seconds == 0 {
+            return usize::MAX;
+        }
+        self.sample_rate as usize * self.max_recording_seconds as usize + self.preroll_capacity()
+    }
+
+    fn preroll_capacity(&self) -> usize {
+        if self.always_warm {
+            (self.sample_rate as u64 * self.preroll_ms as u64 / 1000) as usize
+        } else {
+            0
+        }
+    }
+
+    /// Whether the open stream is alive and matches the configured device and mix
+    fn stream_is_usable(&self) -> bool {
+        self.stream.is_some()
+            && self.stream_alive.load(Ordering::Acquire)
+            && self.stream_preferred_device == self.preferred_device
+            && self.stream_channel_mix == self.channel_mix
+    }
+
+    fn close_stream(&mut self) {
+        self.stream = None;
+        self.on_device_lost = None;
+    }
+
+    /// Resolve the preferred device (with fallback) and open a new stream on it
+    fn open_selected_device(&mut self, on_device_lost: DeviceLostCallback) -> Result<(), Box<dyn std::error::Error>> {
+        // Release the previous device before opening a new one
+        self.stream = None;
+
+        let selected = open_input_device(self.preferred_device.as_deref())?;
+        self.missing_device = if selected.fell_back {
+            self.preferred_device.clone()
+        } else {
+            None
+        };
+
+        let config = selected.device.default_input_config()?;
+
+        // The selected device may differ from the one seen at construction
+        self.sample_rate = config.sample_rate().0;
+        self.device_name = Some(selected.name);
+        self.on_device_lost = Some(on_device_lost);
+
+        // Pre-roll captured from another device or sample rate must not leak into this one
+        *self.buffers.preroll.lock().unwrap() = PrerollBuffer::new(self.preroll_capacity());
+
+        self.open_stream(&selected.device, config)?;
+        self.stream_preferred_device = self.preferred_device.clone();
+        self.stream_channel_mix = self.channel_mix.clone();
+        Ok(())
+    }
+
+    /// Build and start an input stream that writes to the shared buffers
+    fn open_stream(
+        &mut self,
+        device: &Device,
+        config: SupportedStreamConfig,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let stream_config: StreamConfig = config.config();
+        let buffers = self.buffers.clone();
+
+        // Report the loss only once per stream; backends may repeat the error
+        let stream_alive = Arc::new(AtomicBool::new(true));
+        self.stream_alive = Arc::clone(&stream_alive);
+        let on_device_lost = self.on_device_lost.clone();
+
+        let err_fn = move |err: cpal::StreamError| {
+            tracing::error!("Audio stream error: {}", err);
+            if matches!(err, cpal::StreamError::DeviceNotAvailable)
+                && stream_alive.swap(false, Ordering::AcqRel)
+            {
+                if let Some(callback) = &on_device_lost {
+                    callback();
+                }
+            }
+        };
+
+        let stream = match config.sample_format() {
+            cpal::SampleFormat::I8 => self.build_stream::<i8>(device, &stream_config, buffers, err_fn)?,
+            cpal::SampleFormat::I16 => self.build_stream::<i16>(device, &stream_config, buffers, err_fn)?,
+            cpal::SampleFormat::I32 => self.build_stream::<i32>(device, &stream_config, buffers, err_fn)?,
+            cpal::SampleFormat::I64 => self.build_stream::<i64>(device, &stream_config, buffers, err_fn)?,
+            cpal::SampleFormat::U8 => self.build_stream::<u8>(device, &stream_config, buffers, err_fn)?,
+            cpal::SampleFormat::U16 => self.build_stream::<u16>(device, &stream_config, buffers, err_fn)?,
+            cpal::SampleFormat::U32 => self.build_stream::<u32>(device, &stream_config, buffers, err_fn)?,
+            cpal::SampleFormat::U64 => self.build_stream::<u64>(device, &stream_config, buffers, err_fn)?,
+            cpal::SampleFormat::F32 => self.build_stream::<f32>(device, &stream_config, buffers, err_fn)?,
+            cpal::SampleFormat::F64 => self.build_stream::<f64>(device, &stream_config, buffers, err_fn)?,
+            sample_format => {
+                return Err(format!("Unsupported sample format: {}", sample_format).into())
+            }
+        };
+
+        stream.play()?;
+        self.stream = Some(stream);
+
+        Ok(())
+    }
+
     fn build_stream<T>(
         &self,
         device: &Device,
         config: &StreamConfig,
-        buffer: Arc<Mutex<Vec<f32>>>,
+        buffers: SharedBuffers,
         err_fn: impl FnMut(cpal::StreamError) + Send + 'static,
     ) -> Result<Stream, Box<dyn std::error::Error>>
     where
         T: cpal::Sample + cpal::SizedSample,
         f32: cpal::FromSample<T>,
     {
-        let channels = config.channels as usize;
+        let mixer = ChannelMixer::new(&self.channel_mix, config.channels as usize);
+        let level_meter = Arc::clone(&self.level_meter);
+        // Allocated here so the realtime callback does not grow it
+        let frames = match config.buffer_size {
+            cpal::BufferSize::Fixed(frames) => MAX_CALLBACK_FRAMES.max(frames as usize),
+            cpal::BufferSize::Default => MAX_CALLBACK_FRAMES,
+        };
+        let mut mono: Vec<f32> = Vec::with_capacity(frames);
 
         let stream = device.build_input_stream(
             config,
             move |data: &[T], _: &cpal::InputCallbackInfo| {
-                let mut buffer = buffer.lock().unwrap();
-
                 // Convert to mono f32
-                for frame in data.chunks(channels) {
-                    let mono_sample: f32 = frame.iter()
-                        .map(|&s| f32::from_sample(s))
-                        .sum::<f32>() / channels as f32;
-                    buffer.push(mono_sample);
-                }
+                mono.clear();
+                mixer.mix_into(data, &mut mono);
+
+                level_meter.update(&mono);
+                buffers.write(&mono);
             },
             err_fn,
             None,
@@ -100,3 +470,58 @@ impl AudioCapture {
         Ok(stream)
     }
 }
+
+impl Drop for AudioCapture {
+    fn drop(&mut self) {
+        self.stop_chunk_allocator();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preroll_keeps_most_recent_samples() {
+        let mut preroll = PrerollBuffer::new(4);
+        preroll.push(&[1.0, 2.0, 3.0]);
+        preroll.push(&[4.0, 5.0]);
+
+        let out = RecordingBuffer::new(usize::MAX);
+        out.push(&[0.0]);
+        preroll.drain_into(&out);
+        assert_eq!(out.take().samples, vec![0.0, 2.0, 3.0, 4.0, 5.0]);
+        assert!(preroll.samples.is_empty());
+    }
+
+    #[test]
+    fn test_preroll_capacity_changes() {
+        let mut preroll = PrerollBuffer::new(2);
+        preroll.push(&[1.0, 2.0, 3.0]);
+        assert_eq!(preroll.samples, [2.0, 3.0]);
+
+        preroll.set_capacity(1);
+        assert_eq!(preroll.samples, [3.0]);
+
+        preroll.set_capacity(0);
+        preroll.push(&[4.0]);
+        assert!(preroll.samples.is_empty());
+    }
+
+    #[test]
+    fn test_shared_buffers_routing() {
+        let buffers = SharedBuffers {
+            samples: Arc::new(RecordingBuffer::new(usize::MAX)),
+            preroll: Arc::new(Mutex::new(PrerollBuffer::new(8))),
+            recording: Arc::new(AtomicBool::new(false)),
+        };
+
+        buffers.write(&[1.0, 2.0]);
+        assert_eq!(buffers.samples.len(), 0);
+
+        buffers.recording.store(true, Ordering::Release);
+        buffers.write(&[3.0]);
+        assert_eq!(buffers.samples.take().samples, vec![3.0]);
+        assert_eq!(buffers.preroll.lock().unwrap().samples, [1.0, 2.0]);
+    }
+}
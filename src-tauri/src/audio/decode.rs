@@ -0,0 +1,156 @@
+use std::fs::File;
+use std::path::Path;
+
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{DecoderOptions, CODEC_TYPE_NULL};
+use symphonia::core::errors::Error as SymphoniaError;
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+
+use super::mix::ChannelMixer;
+use crate::config::ChannelMix;
+
+/// File extensions accepted by `decode_file`
+pub const SUPPORTED_EXTENSIONS: &[&str] = &["wav", "mp3", "flac", "ogg", "oga"];
+
+/// Mono audio decoded from a file
+pub struct DecodedAudio {
+    pub samples: Vec<f32>,
+    pub sample_rate: u32,
+}
+
+/// Decode an audio file (WAV/MP3/FLAC/OGG Vorbis) to mono f32.
+/// `on_progress` receives the decoded fraction (0.0-1.0) when the length is known.
+pub fn decode_file(
+    path: &Path,
+    mut on_progress: impl FnMut(f32),
+) -> Result<DecodedAudio, Box<dyn std::error::Error>> {
+    let file = File::open(path)?;
+    let mss = MediaSourceStream::new(Box::new(file), Default::default());
+
+    let mut hint = Hint::new();
+    if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
+        hint.with_extension(ext);
+    }
+
+    let probed = symphonia::default::get_probe().format(
+        &hint,
+        mss,
+        &FormatOptions::default(),
+        &MetadataOptions::default(),
+    )?;
+    let mut format = probed.format;
+
+    let track = format
+        .tracks()
+        .iter()
+        .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
+        .ok_or("No audio track found")?;
+    let track_id = track.id;
+    let total_frames = track.codec_params.n_frames;
+    let mut sample_rate = track.codec_params.sample_rate;
+
+    let mut decoder = symphonia::default::get_codecs().make(&track.codec_params, &DecoderOptions::default())?;
+
+    let mut samples = Vec::new();
+    let mut sample_buf: Option<SampleBuffer<f32>> = None;
+    let mut last_progress = 0.0f32;
+
+    loop {
+        let packet = match format.next_packet() {
+            Ok(packet) => packet,
+            // End of stream
+            Err(SymphoniaError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
+            Err(SymphoniaError::ResetRequired) => break,
+            Err(e) => return Err(e.into()),
+        };
+        if packet.track_id() != track_id {
+            continue;
+        }
+
+        let decoded = match decoder.decode(&packet) {
+            Ok(decoded) => decoded,
+            Err(SymphoniaError::DecodeError(e)) => {
+                // Skip corrupt packets like most players do
+                tracing::warn!("Skipping undecodable packet: {}", e);
+                continue;
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        let spec = *decoded.spec();
+        sample_rate.get_or_insert(spec.rate);
+        let buf = sample_buf.get_or_insert_with(|| SampleBuffer::new(decoded.capacity() as u64, spec));
+        if buf.capacity() < decoded.capacity() * spec.channels.count() {
+            *buf = SampleBuffer::new(decoded.capacity() as u64, spec);
+        }
+        buf.copy_interleaved_ref(decoded);
+
+        ChannelMixer::new(&ChannelMix::Average, spec.channels.count()).mix_into(buf.samples(), &mut samples);
+
+        if let Some(total) = total_frames.filter(|&t| t > 0) {
+            let progress = ((packet.ts() + packet.dur()) as f32 / total as f32).min(1.0);
+            // Report in 1% steps
+            if progress - last_progress >= 0.01 {
+                last_progress = progress;
+                on_progress(progress);
+            }
+        }
+    }
+
+    let sample_rate = sample_rate.ok_or("Unknown sample rate")?;
+    if samples.is_empty() {
+        return Err("No audio samples decoded".into());
+    }
+    on_progress(1.0);
+
+    tracing::info!(
+        "Decoded {}: {} samples at {} Hz",
+        path.display(),
+        samples.len(),
+        sample_rate
+    );
+
+    Ok(DecodedAudio {
+        samples,
+        sample_rate,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_stereo_wav() {
+        let path = std::env::temp_dir().join(format!("decode-test-{}.wav", std::process::id()));
+        let spec = hound::WavSpec {
+            channels: 2,
+            sample_rate: 44100,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+        for _ in 0..1000 {
+            writer.write_sample(i16::MAX / 2).unwrap();
+            writer.write_sample(0i16).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        let mut progress = Vec::new();
+        let decoded = decode_file(&path, |p| progress.push(p)).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(decoded.sample_rate, 44100);
+        assert_eq!(decoded.samples.len(), 1000);
+        assert!((decoded.samples[0] - 0.25).abs() < 1e-3);
+        assert_eq!(progress.last(), Some(&1.0));
+    }
+
+    #[test]
+    fn test_decode_missing_file() {
+        assert!(decode_file(Path::new("does-not-exist.wav"), |_| {}).is_err());
+    }
+}
@@ -0,0 +1,152 @@
+use cpal::traits::{DeviceTrait, HostTrait};
+use cpal::{Device, SampleRate, SupportedStreamConfig};
+use serde::Serialize;
+
+/// A stream configuration range supported by an input device
+#[derive(Debug, Clone, Serialize)]
+pub struct SupportedConfigInfo {
+    pub channels: u16,
+    pub min_sample_rate: u32,
+    pub max_sample_rate: u32,
+    pub sample_format: String,
+}
+
+/// An input device available on the default host
+#[derive(Debug, Clone, Serialize)]
+pub struct InputDeviceInfo {
+    /// Device name (also used as the persisted device ID)
+    pub name: String,
+    /// Whether this is the OS default input device
+    pub is_default: bool,
+    pub supported_configs: Vec<SupportedConfigInfo>,
+}
+
+/// The device chosen by `open_input_device`
+pub struct SelectedDevice {
+    pub device: Device,
+    pub name: String,
+    /// Whether the preferred device was not found and the default device was used instead
+    pub fell_back: bool,
+}
+
+/// Enumerate all input devices on the default host
+pub fn list_input_devices() -> Result<Vec<InputDeviceInfo>, Box<dyn std::error::Error>> {
+    let host = cpal::default_host();
+    let default_name = host.default_input_device().and_then(|d| d.name().ok());
+
+    let mut devices = Vec::new();
+    for device in host.input_devices()? {
+        let name = match device.name() {
+            Ok(name) => name,
+            Err(e) => {
+                tracing::warn!("Skipping input device without a name: {}", e);
+                continue;
+            }
+        };
+
+        let supported_configs = device
+            .supported_input_configs()
+            .map(|configs| {
+                configs
+                    .map(|c| SupportedConfigInfo {
+                        channels: c.channels(),
+                        min_sample_rate: c.min_sample_rate().0,
+                        max_sample_rate: c.max_sample_rate().0,
+                        sample_format: c.sample_format().to_string(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        devices.push(InputDeviceInfo {
+            is_default: default_name.as_deref() == Some(name.as_str()),
+            name,
+            supported_configs,
+        });
+    }
+
+    Ok(devices)
+}
+
+/// Find an input device by name
+fn find_input_device(name: &str) -> Option<Device> {
+    let host = cpal::default_host();
+    let mut devices = host.input_devices().ok()?;
+    devices.find(|d| d.name().map(|n| n == name).unwrap_or(false))
+}
+
+/// Open the preferred input device, falling back to the default device if it is unavailable
+pub fn open_input_device(preferred: Option<&str>) -> Result<SelectedDevice, Box<dyn std::error::Error>> {
+    if let Some(name) = preferred {
+        if let Some(device) = find_input_device(name) {
+            return Ok(SelectedDevice {
+                device,
+                name: name.to_string(),
+                fell_back: false,
+            });
+        }
+        tracing::warn!("Input device '{}' not found, falling back to default", name);
+    }
+
+    let device = cpal::default_host()
+        .default_input_device()
+        .ok_or("No input device available")?;
+    let name = device.name().unwrap_or_else(|_| "Unknown".to_string());
+
+    Ok(SelectedDevice {
+        device,
+        name,
+        fell_back: preferred.is_some(),
+    })
+}
+
+/// All input devices in reconnect priority order: the preferred device,
+/// then the OS default device, then the remaining devices
+pub fn input_devices_by_priority(preferred: Option<&str>) -> Vec<(String, Device)> {
+    let host = cpal::default_host();
+    let default_name = host.default_input_device().and_then(|d| d.name().ok());
+
+    let mut devices: Vec<(String, Device)> = host
+        .input_devices()
+        .map(|devices| {
+            devices
+                .filter_map(|d| d.name().ok().map(|name| (name, d)))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let priority = |name: &str| {
+        if Some(name) == preferred {
+            0
+        } else if Some(name) == default_name.as_deref() {
+            1
+        } else {
+            2
+        }
+    };
+    devices.sort_by_key(|(name, _)| priority(name));
+    devices
+}
+
+/// Find a stream config on `device` that captures at exactly `sample_rate`
+pub fn find_config_with_sample_rate(device: &Device, sample_rate: u32) -> Option<SupportedStreamConfig> {
+    // Prefer the device's own default config when it already matches
+    if let Ok(config) = device.default_input_config() {
+        if config.sample_rate().0 == sample_rate {
+            return Some(config);
+        }
+    }
+
+    let mut ranges: Vec<_> = device
+        .supported_input_configs()
+        .ok()?
+        .filter(|c| c.min_sample_rate().0 <= sample_rate && sample_rate <= c.max_sample_rate().0)
+        .collect();
+    // Prefer float formats, then fewer channels
+    ranges.sort_by_key(|c| (!c.sample_format().is_float(), c.channels()));
+
+    ranges
+        .into_iter()
+        .next()
+        .map(|c| c.with_sample_rate(SampleRate(sample_rate)))
+}
@@ -0,0 +1,185 @@
+use std::collections::VecDeque;
+
+use super::vad::VoiceDetector;
+
+/// Level of digital silence, so `log10(0)` never reaches the math
+const MIN_DB: f32 = -90.0;
+/// Chunks quieter than this are never speech
+const SPEECH_FLOOR_DB: f32 = -55.0;
+/// Level above the noise floor at which a chunk has a speech probability of 0.5
+const SNR_MARGIN_DB: f32 = 12.0;
+/// Width of the transition from non-speech to speech around the margin
+const SNR_SLOPE_DB: f32 = 3.0;
+/// Noise floor estimate at the start of a recording
+const INITIAL_NOISE_FLOOR_DB: f32 = -60.0;
+/// The noise floor is the quietest chunk of the last ~3 seconds.
+/// Speech always has short pauses within that time, so it is not mistaken for noise.
+const NOISE_WINDOW_CHUNKS: usize = 94;
+/// Zero crossings per sample above which a chunk sounds like broadband noise
+/// (white noise crosses zero on about half of the samples, voiced speech far less)
+const MAX_SPEECH_ZCR: f32 = 0.3;
+/// Probability multiplier for chunks with a noise-like zero-crossing rate
+const NOISE_ZCR_PENALTY: f32 = 0.2;
+
+/// Lightweight VAD based on signal energy and zero-crossing rate.
+///
+/// A chunk is speech when it is clearly louder than the tracked background noise
+/// and its zero-crossing rate is below that of broadband noise. Needs no model,
+/// so it is used when the Silero model cannot be loaded.
+pub struct EnergyDetector {
+    /// Levels (dBFS) of the most recent chunks
+    recent_db: VecDeque<f32>,
+}
+
+impl EnergyDetector {
+    pub fn new() -> Self {
+        Self {
+            recent_db: std::iter::repeat_n(INITIAL_NOISE_FLOOR_DB, NOISE_WINDOW_CHUNKS).collect(),
+        }
+    }
+
+    fn noise_floor_db(&self) -> f32 {
+        self.recent_db.iter().copied().fold(f32::INFINITY, f32::min)
+    }
+}
+
+impl Default for EnergyDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// RMS level in dBFS
+fn rms_db(chunk: &[f32]) -> f32 {
+    if chunk.is_empty() {
+        return MIN_DB;
+    }
+    let mean_square = chunk.iter().map(|&s| s * s).sum::<f32>() / chunk.len() as f32;
+    (10.0 * mean_square.log10()).max(MIN_DB)
+}
+
+/// Fraction of adjacent sample pairs with a sign change
+fn zero_crossing_rate(chunk: &[f32]) -> f32 {
+    if chunk.len() < 2 {
+        return 0.0;
+    }
+    let crossings = chunk
+        .windows(2)
+        .filter(|pair| (pair[0] >= 0.0) != (pair[1] >= 0.0))
+        .count();
+    crossings as f32 / (chunk.len() - 1) as f32
+}
+
+impl VoiceDetector for EnergyDetector {
+    fn speech_probability(&mut self, chunk: &[f32]) -> f32 {
+        let level_db = rms_db(chunk);
+        self.recent_db.pop_front();
+        self.recent_db.push_back(level_db);
+
+        let snr_db = level_db - self.noise_floor_db();
+        let mut probability = 1.0 / (1.0 + (-(snr_db - SNR_MARGIN_DB) / SNR_SLOPE_DB).exp());
+        if level_db < SPEECH_FLOOR_DB {
+            probability = 0.0;
+        }
+        if zero_crossing_rate(chunk) > MAX_SPEECH_ZCR {
+            probability *= NOISE_ZCR_PENALTY;
+        }
+        probability
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    fn name(&self) -> &'static str {
+        "energy"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::vad::{CHUNK_SIZE, SAMPLE_RATE};
+
+    /// Vowel-like tone: 150Hz fundamental with two harmonics
+    fn voiced(chunks: usize, amplitude: f32) -> Vec<f32> {
+        (0..chunks * CHUNK_SIZE)
+            .map(|i| {
+                let t = i as f32 / SAMPLE_RATE as f32;
+                let phase = 2.0 * std::f32::consts::PI * 150.0 * t;
+                amplitude * (0.6 * phase.sin() + 0.3 * (2.0 * phase).sin() + 0.1 * (3.0 * phase).sin())
+            })
+            .collect()
+    }
+
+    /// Deterministic white noise (xorshift)
+    fn noise(chunks: usize, amplitude: f32) -> Vec<f32> {
+        let mut state: u32 = 0x1234_5678;
+        (0..chunks * CHUNK_SIZE)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                amplitude * (state as f32 / u32::MAX as f32 * 2.0 - 1.0)
+            })
+            .collect()
+    }
+
+    fn probabilities(detector: &mut EnergyDetector, audio: &[f32]) -> Vec<f32> {
+        audio
+            .chunks(CHUNK_SIZE)
+            .map(|chunk| detector.speech_probability(chunk))
+            .collect()
+    }
+
+    #[test]
+    fn test_silence_is_not_speech() {
+        let mut detector = EnergyDetector::new();
+        let probs = probabilities(&mut detector, &vec![0.0; CHUNK_SIZE * 10]);
+        assert!(probs.iter().all(|&p| p == 0.0), "{:?}", probs);
+    }
+
+    #[test]
+    fn test_tone_after_silence_is_speech() {
+        let mut detector = EnergyDetector::new();
+        probabilities(&mut detector, &vec![0.0; CHUNK_SIZE * 10]);
+        // ~1 second of voice is not absorbed into the noise floor
+        let probs = probabilities(&mut detector, &voiced(30, 0.3));
+        assert!(probs.iter().all(|&p| p > 0.9), "{:?}", probs);
+    }
+
+    #[test]
+    fn test_white_noise_is_not_speech() {
+        let mut detector = EnergyDetector::new();
+        let probs = probabilities(&mut detector, &noise(20, 0.3));
+        assert!(probs.iter().all(|&p| p < 0.5), "{:?}", probs);
+    }
+
+    #[test]
+    fn test_speech_over_background_noise() {
+        let mut detector = EnergyDetector::new();
+        // Steady hum (about -40dBFS) becomes the noise floor once it fills the window
+        let probs = probabilities(&mut detector, &voiced(NOISE_WINDOW_CHUNKS + 5, 0.02));
+        assert!(probs[NOISE_WINDOW_CHUNKS..].iter().all(|&p| p < 0.5), "{:?}", probs);
+
+        let probs = probabilities(&mut detector, &voiced(10, 0.3));
+        assert!(probs.iter().all(|&p| p > 0.9), "{:?}", probs);
+    }
+
+    #[test]
+    fn test_reset_restores_noise_floor() {
+        let mut detector = EnergyDetector::new();
+        probabilities(&mut detector, &vec![0.0; CHUNK_SIZE]);
+        assert_eq!(detector.noise_floor_db(), MIN_DB);
+        detector.reset();
+        assert_eq!(detector.noise_floor_db(), INITIAL_NOISE_FLOOR_DB);
+    }
+
+    #[test]
+    fn test_zero_crossing_rate() {
+        assert_eq!(zero_crossing_rate(&[1.0, -1.0, 1.0, -1.0, 1.0]), 1.0);
+        assert_eq!(zero_crossing_rate(&[0.5; 8]), 0.0);
+        assert!(zero_crossing_rate(&voiced(1, 0.3)) < MAX_SPEECH_ZCR);
+        assert!(zero_crossing_rate(&noise(1, 0.3)) > MAX_SPEECH_ZCR);
+    }
+}
@@ -0,0 +1,231 @@
+use serde::Serialize;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Peak amplitude at or above this is treated as clipping
+const CLIP_THRESHOLD: f32 = 0.99;
+/// RMS level below this is treated as silence (e.g. a muted microphone)
+const SILENCE_THRESHOLD_DB: f32 = -60.0;
+/// Floor used when converting zero amplitude to dBFS
+const MIN_DB: f32 = -100.0;
+/// Continuous silence required before warning
+const SILENCE_WARNING_AFTER: Duration = Duration::from_secs(3);
+/// Minimum interval between repeated clipping warnings
+const CLIPPING_WARNING_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Input level over a metering interval
+#[derive(Debug, Clone, Serialize)]
+pub struct AudioLevel {
+    pub rms: f32,
+    pub peak: f32,
+    pub rms_db: f32,
+    pub peak_db: f32,
+    pub clipping: bool,
+    pub silent: bool,
+}
+
+impl AudioLevel {
+    fn new(rms: f32, peak: f32) -> Self {
+        let rms_db = to_db(rms);
+        Self {
+            rms,
+            peak,
+            rms_db,
+            peak_db: to_db(peak),
+            clipping: peak >= CLIP_THRESHOLD,
+            silent: rms_db < SILENCE_THRESHOLD_DB,
+        }
+    }
+}
+
+/// Convert a linear amplitude to dBFS
+pub fn to_db(amplitude: f32) -> f32 {
+    if amplitude <= 0.0 {
+        MIN_DB
+    } else {
+        (20.0 * amplitude.log10()).max(MIN_DB)
+    }
+}
+
+/// Level accumulator shared between the realtime audio callback and the event emitter.
+/// Only atomics are used so the callback never blocks on it.
+pub struct LevelMeter {
+    /// Sum of squared samples (f64 bits)
+    sum_squares: AtomicU64,
+    count: AtomicU64,
+    /// Peak absolute amplitude (f32 bits)
+    peak: AtomicU32,
+}
+
+impl LevelMeter {
+    pub fn new() -> Self {
+        Self {
+            sum_squares: AtomicU64::new(0f64.to_bits()),
+            count: AtomicU64::new(0),
+            peak: AtomicU32::new(0f32.to_bits()),
+        }
+    }
+
+    /// Accumulate a block of mono samples (called from the audio callback)
+    pub fn update(&self, samples: &[f32]) {
+        if samples.is_empty() {
+            return;
+        }
+        let block_sum: f64 = samples.iter().map(|&s| (s as f64) * (s as f64)).sum();
+        let block_peak = samples.iter().fold(0.0f32, |acc, &s| acc.max(s.abs()));
+
+        let _ = self
+            .sum_squares
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |bits| {
+                Some((f64::from_bits(bits) + block_sum).to_bits())
+            });
+        self.count.fetch_add(samples.len() as u64, Ordering::AcqRel);
+        let _ = self.peak.fetch_update(Ordering::AcqRel, Ordering::Acquire, |bits| {
+            (block_peak > f32::from_bits(bits)).then(|| block_peak.to_bits())
+        });
+    }
+
+    /// Return the level accumulated since the last call and reset the meter.
+    /// Returns None when no samples arrived in the interval.
+    pub fn take(&self) -> Option<AudioLevel> {
+        let count = self.count.swap(0, Ordering::AcqRel);
+        let sum_squares = f64::from_bits(self.sum_squares.swap(0f64.to_bits(), Ordering::AcqRel));
+        let peak = f32::from_bits(self.peak.swap(0f32.to_bits(), Ordering::AcqRel));
+
+        if count == 0 {
+            return None;
+        }
+        Some(AudioLevel::new((sum_squares / count as f64).sqrt() as f32, peak))
+    }
+}
+
+impl Default for LevelMeter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Warnings derived from the level stream
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum LevelWarning {
+    /// The input has been silent for a while (muted or wrong microphone)
+    Silence,
+    /// The input is clipping (gain too high)
+    Clipping,
+}
+
+/// Turns periodic level readings into rate-limited warnings
+pub struct LevelMonitor {
+    silent_for: Duration,
+    silence_warned: bool,
+    since_clipping_warning: Option<Duration>,
+}
+
+impl LevelMonitor {
+    pub fn new() -> Self {
+        Self {
+            silent_for: Duration::ZERO,
+            silence_warned: false,
+            since_clipping_warning: None,
+        }
+    }
+
+    /// Feed one level reading covering `elapsed` time; returns a warning to emit, if any
+    pub fn update(&mut self, level: &AudioLevel, elapsed: Duration) -> Option<LevelWarning> {
+        if let Some(since) = self.since_clipping_warning.as_mut() {
+            *since += elapsed;
+        }
+
+        if level.clipping {
+            let due = self
+                .since_clipping_warning
+                .is_none_or(|since| since >= CLIPPING_WARNING_INTERVAL);
+            if due {
+                self.since_clipping_warning = Some(Duration::ZERO);
+                return Some(LevelWarning::Clipping);
+            }
+        }
+
+        if level.silent {
+            self.silent_for += elapsed;
+            if self.silent_for >= SILENCE_WARNING_AFTER && !self.silence_warned {
+                self.silence_warned = true;
+                return Some(LevelWarning::Silence);
+            }
+        } else {
+            self.silent_for = Duration::ZERO;
+            self.silence_warned = false;
+        }
+
+        None
+    }
+}
+
+impl Default for LevelMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Level of a whole block, computed directly
+    fn compute_level(samples: &[f32]) -> AudioLevel {
+        if samples.is_empty() {
+            return AudioLevel::new(0.0, 0.0);
+        }
+        let sum_squares: f64 = samples.iter().map(|&s| (s as f64) * (s as f64)).sum();
+        let peak = samples.iter().fold(0.0f32, |acc, &s| acc.max(s.abs()));
+        AudioLevel::new((sum_squares / samples.len() as f64).sqrt() as f32, peak)
+    }
+
+    #[test]
+    fn test_sine_level() {
+        let samples: Vec<f32> = (0..48000)
+            .map(|i| 0.5 * (2.0 * std::f32::consts::PI * 440.0 * i as f32 / 48000.0).sin())
+            .collect();
+        let level = compute_level(&samples);
+        assert!((level.rms - 0.5 / 2f32.sqrt()).abs() < 0.01);
+        assert!((level.peak - 0.5).abs() < 0.01);
+        assert!((level.peak_db - -6.02).abs() < 0.1);
+        assert!(!level.clipping);
+        assert!(!level.silent);
+    }
+
+    #[test]
+    fn test_clipping_and_silence() {
+        assert!(compute_level(&[0.0, 1.0, -1.0, 0.2]).clipping);
+        assert!(compute_level(&[0.0; 512]).silent);
+        assert_eq!(to_db(0.0), MIN_DB);
+    }
+
+    #[test]
+    fn test_meter_matches_block_level() {
+        let meter = LevelMeter::new();
+        let samples: Vec<f32> = (0..1000).map(|i| ((i % 7) as f32 - 3.0) / 10.0).collect();
+        meter.update(&samples[..400]);
+        meter.update(&samples[400..]);
+
+        let level = meter.take().unwrap();
+        let expected = compute_level(&samples);
+        assert!((level.rms - expected.rms).abs() < 1e-6);
+        assert_eq!(level.peak, expected.peak);
+        assert!(meter.take().is_none());
+    }
+
+    #[test]
+    fn test_monitor_warnings() {
+        let tick = Duration::from_millis(100);
+        let mut monitor = LevelMonitor::new();
+        let silent = AudioLevel::new(0.0, 0.0);
+        let clipping = AudioLevel::new(0.5, 1.0);
+
+        let warnings: Vec<_> = (0..40).filter_map(|_| monitor.update(&silent, tick)).collect();
+        assert_eq!(warnings, vec![LevelWarning::Silence]);
+
+        assert_eq!(monitor.update(&clipping, tick), Some(LevelWarning::Clipping));
+        assert_eq!(monitor.update(&clipping, tick), None);
+    }
+}
@@ -0,0 +1,147 @@
+use cpal::{FromSample, Sample};
+
+use crate::config::ChannelMix;
+
+/// Mixes interleaved multi-channel frames down to mono f32
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChannelMixer {
+    Average { channels: usize },
+    Single { channels: usize, channel: usize },
+    Weighted { gains: Vec<f32> },
+}
+
+impl ChannelMixer {
+    /// Build a mixer for a device with `channels` channels. Settings that do not fit
+    /// the device (missing channel, all-zero gains) fall back to averaging.
+    pub fn new(mix: &ChannelMix, channels: usize) -> Self {
+        let channels = channels.max(1);
+        match mix {
+            ChannelMix::Average => Self::Average { channels },
+            ChannelMix::Single { channel } if *channel < channels => Self::Single {
+                channels,
+                channel: *channel,
+            },
+            ChannelMix::Single { channel } => {
+                tracing::warn!(
+                    "Channel {} not available on a {}-channel device, averaging all channels",
+                    channel + 1,
+                    channels
+                );
+                Self::Average { channels }
+            }
+            ChannelMix::Custom { gains } => {
+                let mut gains = gains.clone();
+                if gains.len() != channels {
+                    tracing::warn!(
+                        "Channel mix has {} gains for a {}-channel device",
+                        gains.len(),
+                        channels
+                    );
+                }
+                gains.resize(channels, 0.0);
+                if gains.iter().all(|&g| g == 0.0) {
+                    tracing::warn!("Channel mix gains are all zero, averaging all channels");
+                    return Self::Average { channels };
+                }
+                Self::Weighted { gains }
+            }
+        }
+    }
+
+    fn channels(&self) -> usize {
+        match self {
+            Self::Average { channels } | Self::Single { channels, .. } => *channels,
+            Self::Weighted { gains } => gains.len(),
+        }
+    }
+
+    /// Convert interleaved samples of any cpal format to mono f32, appending to `out`
+    pub fn mix_into<T>(&self, data: &[T], out: &mut Vec<f32>)
+    where
+        T: Sample,
+        f32: FromSample<T>,
+    {
+        let frames = data.chunks_exact(self.channels());
+        match self {
+            Self::Average { channels } => out.extend(frames.map(|frame| {
+                frame.iter().map(|&s| f32::from_sample(s)).sum::<f32>() / *channels as f32
+            })),
+            Self::Single { channel, .. } => {
+                out.extend(frames.map(|frame| f32::from_sample(frame[*channel])))
+            }
+            Self::Weighted { gains } => out.extend(frames.map(|frame| {
+                frame
+                    .iter()
+                    .zip(gains)
+                    .map(|(&s, &g)| f32::from_sample(s) * g)
+                    .sum::<f32>()
+            })),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mix<T>(mix: &ChannelMix, channels: usize, data: &[T]) -> Vec<f32>
+    where
+        T: Sample,
+        f32: FromSample<T>,
+    {
+        let mut out = Vec::new();
+        ChannelMixer::new(mix, channels).mix_into(data, &mut out);
+        out
+    }
+
+    #[test]
+    fn test_average() {
+        let out = mix(&ChannelMix::Average, 2, &[0.5f32, -0.5, 1.0, 0.0]);
+        assert_eq!(out, vec![0.0, 0.5]);
+    }
+
+    #[test]
+    fn test_single_channel_of_four() {
+        let frames = [0.1f32, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8];
+        let out = mix(&ChannelMix::Single { channel: 1 }, 4, &frames);
+        assert_eq!(out, vec![0.2, 0.6]);
+    }
+
+    #[test]
+    fn test_custom_gains() {
+        let gains = ChannelMix::Custom { gains: vec![1.0, 0.0, 0.5] };
+        let out = mix(&gains, 3, &[0.2f32, 0.9, 0.4]);
+        assert!((out[0] - 0.4).abs() < 1e-6);
+
+        // Missing gains are treated as 0
+        let out = mix(&ChannelMix::Custom { gains: vec![0.5] }, 2, &[0.8f32, 1.0]);
+        assert!((out[0] - 0.4).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_invalid_settings_fall_back_to_average() {
+        assert_eq!(
+            ChannelMixer::new(&ChannelMix::Single { channel: 4 }, 4),
+            ChannelMixer::Average { channels: 4 }
+        );
+        assert_eq!(
+            ChannelMixer::new(&ChannelMix::Custom { gains: vec![0.0, 0.0] }, 2),
+            ChannelMixer::Average { channels: 2 }
+        );
+    }
+
+    #[test]
+    fn test_integer_and_float_formats() {
+        let single = ChannelMix::Single { channel: 0 };
+        assert_eq!(mix(&single, 1, &[i8::MIN, 0i8]), vec![-1.0, 0.0]);
+        assert_eq!(mix(&single, 1, &[i32::MIN, 0i32]), vec![-1.0, 0.0]);
+        assert_eq!(mix(&single, 1, &[u8::MIN, 128u8]), vec![-1.0, 0.0]);
+        assert_eq!(mix(&single, 1, &[u32::MIN, 1u32 << 31]), vec![-1.0, 0.0]);
+        assert_eq!(mix(&single, 1, &[i64::MIN, 0i64]), vec![-1.0, 0.0]);
+        assert_eq!(mix(&single, 1, &[0.25f64, -0.5]), vec![0.25, -0.5]);
+
+        let out = mix(&ChannelMix::Average, 2, &[i16::MAX, i16::MAX, 0i16, 0]);
+        assert!((out[0] - 1.0).abs() < 1e-4);
+        assert_eq!(out[1], 0.0);
+    }
+}
@@ -1,7 +1,17 @@
+pub mod archive;
+pub mod buffer;
 pub mod capture;
+pub mod decode;
+pub mod device;
+pub mod energy_vad;
+pub mod level;
+pub mod mix;
 pub mod resample;
+pub mod utterance;
 pub mod vad;
 
-pub use capture::AudioCapture;
+pub use capture::{AudioCapture, DeviceLostCallback};
+pub use device::{list_input_devices, InputDeviceInfo};
+pub use level::{LevelMonitor, LevelWarning};
 pub use resample::Resampler;
 pub use vad::VadProcessor;
@@ -0,0 +1,380 @@
+use std::collections::VecDeque;
+
+use super::resample::StreamingResampler;
+use super::vad::{VadProcessor, CHUNK_SIZE, SAMPLE_RATE};
+use crate::config::{ResampleQuality, VadSettings};
+
+/// Timing of utterance detection, in 32ms VAD chunks
+#[derive(Debug, Clone, PartialEq)]
+pub struct UtteranceConfig {
+    /// Speech probability above which a chunk counts as speech
+    pub threshold: f32,
+    /// Chunks kept before the first and after the last speech chunk
+    pub padding_chunks: usize,
+    /// Consecutive non-speech chunks that end an utterance
+    pub silence_chunks: usize,
+    /// Utterances with fewer speech chunks are discarded as noise
+    pub min_speech_chunks: usize,
+    /// Utterances are cut after this many chunks (0 = unlimited)
+    pub max_chunks: usize,
+}
+
+impl UtteranceConfig {
+    pub fn new(vad: &VadSettings, silence_timeout_ms: u32, min_speech_ms: u32, max_seconds: u32) -> Self {
+        let chunk_ms = CHUNK_SIZE as u64 * 1000 / SAMPLE_RATE as u64;
+        let to_chunks = |ms: u64| ms.div_ceil(chunk_ms) as usize;
+        Self {
+            threshold: vad.threshold,
+            padding_chunks: vad.padding_chunks,
+            silence_chunks: to_chunks(silence_timeout_ms as u64).max(1),
+            min_speech_chunks: to_chunks(min_speech_ms as u64),
+            max_chunks: to_chunks(max_seconds as u64 * 1000),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum UtteranceEvent {
+    /// Speech began
+    Started,
+    /// Speech ended; 16kHz audio of the whole utterance including padding
+    Ended(Vec<f32>),
+    /// Speech ended but was too short to be an utterance
+    Discarded,
+}
+
+struct Utterance {
+    samples: Vec<f32>,
+    chunks: usize,
+    speech_chunks: usize,
+    /// Non-speech chunks since the last speech chunk
+    silent_run: usize,
+}
+
+/// Splits a continuous stream of VAD-labelled chunks into utterances: speech starts
+/// one, and `silence_chunks` of consecutive non-speech end it.
+pub struct UtteranceDetector {
+    config: UtteranceConfig,
+    /// Most recent non-speech chunks, prepended when speech starts
+    preroll: VecDeque<Vec<f32>>,
+    current: Option<Utterance>,
+}
+
+impl UtteranceDetector {
+    pub fn new(config: UtteranceConfig) -> Self {
+        Self {
+            config,
+            preroll: VecDeque::new(),
+            current: None,
+        }
+    }
+
+    /// Whether an utterance is in progress
+    #[cfg(test)]
+    pub fn in_utterance(&self) -> bool {
+        self.current.is_some()
+    }
+
+    /// Feed one chunk and its speech probability
+    pub fn push(&mut self, chunk: &[f32], probability: f32) -> Option<UtteranceEvent> {
+        let is_speech = probability > self.config.threshold;
+
+        let Some(utterance) = self.current.as_mut() else {
+            if !is_speech {
+                self.preroll.push_back(chunk.to_vec());
+                while self.preroll.len() > self.config.padding_chunks {
+                    self.preroll.pop_front();
+                }
+                return None;
+            }
+
+            let mut samples: Vec<f32> = self.preroll.drain(..).flatten().collect();
+            samples.extend_from_slice(chunk);
+            self.current = Some(Utterance {
+                samples,
+                chunks: 1,
+                speech_chunks: 1,
+                silent_run: 0,
+            });
+            return Some(UtteranceEvent::Started);
+        };
+
+        utterance.samples.extend_from_slice(chunk);
+        utterance.chunks += 1;
+        if is_speech {
+            utterance.speech_chunks += 1;
+            utterance.silent_run = 0;
+        } else {
+            utterance.silent_run += 1;
+        }
+
+        let too_long = self.config.max_chunks > 0 && utterance.chunks >= self.config.max_chunks;
+        if utterance.silent_run >= self.config.silence_chunks || too_long {
+            return self.finish();
+        }
+        None
+    }
+
+    /// End the utterance in progress (e.g. when listening stops)
+    pub fn finish(&mut self) -> Option<UtteranceEvent> {
+        let mut utterance = self.current.take()?;
+
+        if utterance.speech_chunks < self.config.min_speech_chunks {
+            return Some(UtteranceEvent::Discarded);
+        }
+
+        // Keep only `padding_chunks` of the trailing silence
+        let trailing = utterance.silent_run.saturating_sub(self.config.padding_chunks);
+        let trailing_samples = (trailing * CHUNK_SIZE).min(utterance.samples.len());
+        utterance.samples.truncate(utterance.samples.len() - trailing_samples);
+        Some(UtteranceEvent::Ended(utterance.samples))
+    }
+}
+
+/// Detects the end of speech in a recording: true once at least `min_speech_chunks`
+/// of speech were heard and are followed by `silence_chunks` of non-speech
+pub struct TrailingSilence {
+    threshold: f32,
+    silence_chunks: usize,
+    min_speech_chunks: usize,
+    speech_chunks: usize,
+    /// Non-speech chunks since the last speech chunk
+    silent_run: usize,
+}
+
+impl TrailingSilence {
+    pub fn new(vad: &VadSettings, silence_ms: u32, min_speech_ms: u32) -> Self {
+        let config = UtteranceConfig::new(vad, silence_ms, min_speech_ms, 0);
+        Self {
+            threshold: config.threshold,
+            silence_chunks: config.silence_chunks,
+            min_speech_chunks: config.min_speech_chunks.max(1),
+            speech_chunks: 0,
+            silent_run: 0,
+        }
+    }
+
+    /// Feed the speech probability of the next chunk
+    pub fn push(&mut self, probability: f32) -> bool {
+        if probability > self.threshold {
+            self.speech_chunks += 1;
+            self.silent_run = 0;
+        } else {
+            self.silent_run += 1;
+        }
+        self.speech_chunks >= self.min_speech_chunks && self.silent_run >= self.silence_chunks
+    }
+}
+
+/// Runs the VAD model on live capture audio: resamples to 16kHz and scores every 32ms chunk
+struct LiveVad {
+    resampler: StreamingResampler,
+    vad: VadProcessor,
+    /// 16kHz samples not yet forming a full VAD chunk
+    pending: Vec<f32>,
+}
+
+impl LiveVad {
+    fn new(
+        input_sample_rate: u32,
+        quality: ResampleQuality,
+        vad_settings: &VadSettings,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(Self {
+            resampler: StreamingResampler::new(input_sample_rate, SAMPLE_RATE as u32, quality)?,
+            vad: VadProcessor::new(vad_settings),
+            pending: Vec::with_capacity(CHUNK_SIZE),
+        })
+    }
+
+    /// Process captured samples (at the input sample rate), calling `on_chunk` with every
+    /// complete 16kHz chunk and its speech probability
+    fn process(
+        &mut self,
+        input: &[f32],
+        mut on_chunk: impl FnMut(&[f32], f32),
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.pending.extend(self.resampler.process(input)?);
+
+        let mut chunks = self.pending.chunks_exact(CHUNK_SIZE);
+        for chunk in &mut chunks {
+            on_chunk(chunk, self.vad.speech_probability(chunk));
+        }
+        let remainder = chunks.remainder().len();
+        self.pending.drain(..self.pending.len() - remainder);
+        Ok(())
+    }
+
+    fn reset(&mut self) {
+        self.pending.clear();
+        self.resampler.reset();
+    }
+
+    fn fell_back(&self) -> bool {
+        self.vad.fell_back()
+    }
+}
+
+/// Voice-activated utterance detection on live capture audio
+pub struct StreamingVad {
+    live: LiveVad,
+    detector: UtteranceDetector,
+}
+
+impl StreamingVad {
+    pub fn new(
+        input_sample_rate: u32,
+        quality: ResampleQuality,
+        vad_settings: &VadSettings,
+        config: UtteranceConfig,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(Self {
+            live: LiveVad::new(input_sample_rate, quality, vad_settings)?,
+            detector: UtteranceDetector::new(config),
+        })
+    }
+
+    /// Process captured samples (at the input sample rate) and return the utterance events
+    pub fn process(&mut self, input: &[f32]) -> Result<Vec<UtteranceEvent>, Box<dyn std::error::Error>> {
+        let mut events = Vec::new();
+        let detector = &mut self.detector;
+        self.live
+            .process(input, |chunk, probability| events.extend(detector.push(chunk, probability)))?;
+        Ok(events)
+    }
+
+    /// Whether the configured VAD engine could not be loaded and `Energy` is used instead
+    pub fn fell_back(&self) -> bool {
+        self.live.fell_back()
+    }
+
+    /// End the utterance in progress, if any
+    pub fn finish(&mut self) -> Option<UtteranceEvent> {
+        self.live.reset();
+        self.detector.finish()
+    }
+}
+
+/// Trailing-silence detection on a live recording, for stopping it automatically
+pub struct SilenceMonitor {
+    live: LiveVad,
+    trailing: TrailingSilence,
+}
+
+impl SilenceMonitor {
+    pub fn new(
+        input_sample_rate: u32,
+        quality: ResampleQuality,
+        vad_settings: &VadSettings,
+        trailing: TrailingSilence,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(Self {
+            live: LiveVad::new(input_sample_rate, quality, vad_settings)?,
+            trailing,
+        })
+    }
+
+    /// Whether the configured VAD engine could not be loaded and `Energy` is used instead
+    pub fn fell_back(&self) -> bool {
+        self.live.fell_back()
+    }
+
+    /// Process captured samples (at the input sample rate).
+    /// Returns true once the speaker has been silent long enough.
+    pub fn process(&mut self, input: &[f32]) -> Result<bool, Box<dyn std::error::Error>> {
+        let mut silent = false;
+        let trailing = &mut self.trailing;
+        self.live
+            .process(input, |_, probability| silent |= trailing.push(probability))?;
+        Ok(silent)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> UtteranceConfig {
+        UtteranceConfig {
+            threshold: 0.5,
+            padding_chunks: 1,
+            silence_chunks: 3,
+            min_speech_chunks: 2,
+            max_chunks: 0,
+        }
+    }
+
+    /// Feed chunks whose samples are their index, with the given probabilities
+    fn feed(detector: &mut UtteranceDetector, probabilities: &[f32]) -> Vec<UtteranceEvent> {
+        probabilities
+            .iter()
+            .enumerate()
+            .filter_map(|(i, &p)| detector.push(&vec![i as f32; CHUNK_SIZE], p))
+            .collect()
+    }
+
+    fn chunk_ids(samples: &[f32]) -> Vec<f32> {
+        samples.chunks(CHUNK_SIZE).map(|c| c[0]).collect()
+    }
+
+    #[test]
+    fn test_utterance_with_padding() {
+        let mut detector = UtteranceDetector::new(config());
+        let events = feed(&mut detector, &[0.1, 0.1, 0.9, 0.8, 0.1, 0.9, 0.1, 0.1, 0.1, 0.1]);
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0], UtteranceEvent::Started);
+        let UtteranceEvent::Ended(samples) = &events[1] else {
+            panic!("expected an utterance, got {:?}", events[1]);
+        };
+        // One chunk of padding on each side, the short pause inside is kept
+        assert_eq!(chunk_ids(samples), vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        assert!(!detector.in_utterance());
+    }
+
+    #[test]
+    fn test_short_noise_is_discarded() {
+        let mut detector = UtteranceDetector::new(config());
+        let events = feed(&mut detector, &[0.9, 0.1, 0.1, 0.1]);
+        assert_eq!(events, vec![UtteranceEvent::Started, UtteranceEvent::Discarded]);
+    }
+
+    #[test]
+    fn test_max_length_and_finish() {
+        let mut detector = UtteranceDetector::new(UtteranceConfig {
+            max_chunks: 4,
+            ..config()
+        });
+        let events = feed(&mut detector, &[0.9; 6]);
+        assert_eq!(events.len(), 3);
+        assert!(matches!(&events[1], UtteranceEvent::Ended(s) if s.len() == 4 * CHUNK_SIZE));
+        assert_eq!(events[2], UtteranceEvent::Started);
+
+        // The remaining speech is returned when listening stops
+        assert_eq!(detector.push(&[0.0; CHUNK_SIZE], 0.9), None);
+        assert!(matches!(detector.finish(), Some(UtteranceEvent::Ended(s)) if s.len() == 3 * CHUNK_SIZE));
+        assert_eq!(detector.finish(), None);
+    }
+
+    #[test]
+    fn test_trailing_silence_after_speech() {
+        // 96ms of silence (3 chunks), at least 64ms of speech (2 chunks)
+        let mut trailing = TrailingSilence::new(&VadSettings::default(), 96, 64);
+
+        // Leading silence and a single noisy chunk do not stop the recording
+        let early: Vec<bool> = [0.1, 0.1, 0.1, 0.1, 0.9, 0.1, 0.1, 0.1].iter().map(|&p| trailing.push(p)).collect();
+        assert!(early.iter().all(|&stop| !stop));
+
+        let late: Vec<bool> = [0.9, 0.1, 0.1, 0.9, 0.1, 0.1, 0.1].iter().map(|&p| trailing.push(p)).collect();
+        assert_eq!(late, [false, false, false, false, false, false, true]);
+    }
+
+    #[test]
+    fn test_config_from_settings() {
+        let config = UtteranceConfig::new(&VadSettings::default(), 800, 250, 0);
+        assert_eq!(config.silence_chunks, 25);
+        assert_eq!(config.min_speech_chunks, 8);
+        assert_eq!(config.max_chunks, 0);
+        assert_eq!(config.padding_chunks, VadSettings::default().padding_chunks);
+    }
+}
@@ -1,83 +1,283 @@
-use voice_activity_detector::{IteratorExt, LabeledAudio, VoiceActivityDetector};
+use voice_activity_detector::VoiceActivityDetector;
 
-const SAMPLE_RATE: i64 = 16000;
-const CHUNK_SIZE: usize = 512; // 512 samples at 16kHz = 32ms
-const SPEECH_THRESHOLD: f32 = 0.5;
-const PADDING_CHUNKS: usize = 3; // Add 3 chunks (~96ms) before/after speech
+use super::energy_vad::EnergyDetector;
+use crate::config::{VadEngine, VadSettings};
 
-pub struct VadProcessor {
+pub const SAMPLE_RATE: i64 = 16000;
+pub const CHUNK_SIZE: usize = 512; // 512 samples at 16kHz = 32ms
+
+/// A run of consecutive speech chunks.
+/// `start` and `end` are sample offsets (16kHz) into the audio passed to the VAD.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpeechSegment {
+    pub start: usize,
+    pub end: usize,
+    pub samples: Vec<f32>,
+}
+
+/// Speech segments concatenated into one buffer, with the position of every
+/// segment so offsets in the joined audio can be mapped back to the recording
+pub struct JoinedSpeech {
+    pub samples: Vec<f32>,
+    /// (offset in `samples`, offset in the original audio, length) per segment
+    spans: Vec<(usize, usize, usize)>,
+}
+
+impl JoinedSpeech {
+    /// Map a sample offset in the joined audio to the original audio.
+    /// Offsets inside an inserted silence map to the end of the preceding segment.
+    pub fn to_original(&self, joined_offset: usize) -> usize {
+        let index = self.spans.partition_point(|&(joined_start, _, _)| joined_start <= joined_offset);
+        match index.checked_sub(1).map(|i| self.spans[i]) {
+            Some((joined_start, original_start, len)) => original_start + (joined_offset - joined_start).min(len),
+            None => joined_offset,
+        }
+    }
+
+    /// Offsets in the joined audio in the middle of each inserted silence,
+    /// where the audio can be split without cutting speech
+    pub fn gap_boundaries(&self) -> Vec<usize> {
+        self.spans
+            .windows(2)
+            .map(|pair| {
+                let (start, _, len) = pair[0];
+                (start + len + pair[1].0) / 2
+            })
+            .collect()
+    }
+}
+
+/// Concatenate speech segments, inserting `gap_samples` of silence between them
+/// so Whisper does not hear words from separate sentences run together
+pub fn join_segments(segments: &[SpeechSegment], gap_samples: usize) -> JoinedSpeech {
+    let total = segments.iter().map(|s| s.samples.len()).sum::<usize>()
+        + gap_samples * segments.len().saturating_sub(1);
+    let mut samples = Vec::with_capacity(total);
+    let mut spans = Vec::with_capacity(segments.len());
+
+    for (i, segment) in segments.iter().enumerate() {
+        if i > 0 {
+            samples.resize(samples.len() + gap_samples, 0.0);
+        }
+        spans.push((samples.len(), segment.start, segment.samples.len()));
+        samples.extend_from_slice(&segment.samples);
+    }
+
+    JoinedSpeech { samples, spans }
+}
+
+/// Mark every chunk within `padding` chunks of a chunk above `threshold` as speech
+fn label_chunks(probabilities: &[f32], threshold: f32, padding: usize) -> Vec<bool> {
+    let mut labels = vec![false; probabilities.len()];
+    for (i, _) in probabilities.iter().enumerate().filter(|&(_, &p)| p > threshold) {
+        let end = (i + padding + 1).min(labels.len());
+        labels[i.saturating_sub(padding)..end].fill(true);
+    }
+    labels
+}
+
+/// Group labeled `CHUNK_SIZE` chunks of `audio` into segments of consecutive speech.
+/// Returns the segments, the total number of chunks and the number of speech chunks.
+fn collect_segments(audio: &[f32], labels: &[bool]) -> (Vec<SpeechSegment>, usize, usize) {
+    let mut segments: Vec<SpeechSegment> = Vec::new();
+    let mut in_speech = false;
+    let mut speech_chunks = 0;
+
+    for (chunk_index, (samples, &is_speech)) in audio.chunks(CHUNK_SIZE).zip(labels).enumerate() {
+        if !is_speech {
+            in_speech = false;
+            continue;
+        }
+
+        speech_chunks += 1;
+        let start = chunk_index * CHUNK_SIZE;
+        let end = start + samples.len();
+        match segments.last_mut() {
+            Some(segment) if in_speech => {
+                segment.end = end;
+                segment.samples.extend_from_slice(samples);
+            }
+            _ => segments.push(SpeechSegment {
+                start,
+                end,
+                samples: samples.to_vec(),
+            }),
+        }
+        in_speech = true;
+    }
+
+    (segments, labels.len(), speech_chunks)
+}
+
+/// Frame-level speech classifier behind `VadProcessor`
+pub trait VoiceDetector {
+    /// Speech probability (0.0-1.0) of one `CHUNK_SIZE` chunk of 16kHz audio.
+    /// Detectors may keep state between calls, so chunks must be passed in order.
+    fn speech_probability(&mut self, chunk: &[f32]) -> f32;
+
+    /// Forget the state from previous audio
+    fn reset(&mut self);
+
+    /// Name for logging
+    fn name(&self) -> &'static str;
+}
+
+/// Silero neural network VAD
+pub struct SileroDetector {
     detector: VoiceActivityDetector,
 }
 
-impl VadProcessor {
+impl SileroDetector {
     pub fn new() -> Result<Self, Box<dyn std::error::Error>> {
         let detector = VoiceActivityDetector::builder()
             .sample_rate(SAMPLE_RATE)
             .chunk_size(CHUNK_SIZE)
             .build()
             .map_err(|e| format!("Failed to create VAD: {:?}", e))?;
+        Ok(Self { detector })
+    }
+}
 
-        tracing::info!("VAD initialized: {}Hz, {} samples/chunk", SAMPLE_RATE, CHUNK_SIZE);
+impl VoiceDetector for SileroDetector {
+    fn speech_probability(&mut self, chunk: &[f32]) -> f32 {
+        self.detector.predict(chunk.iter().copied())
+    }
 
-        Ok(Self { detector })
+    fn reset(&mut self) {
+        self.detector.reset();
+    }
+
+    fn name(&self) -> &'static str {
+        "silero"
+    }
+}
+
+/// Create the detector for `engine`, falling back to the energy detector when the
+/// Silero model cannot be loaded. Returns the detector and the engine actually used.
+fn create_detector(engine: VadEngine) -> (Box<dyn VoiceDetector>, VadEngine) {
+    if engine == VadEngine::Silero {
+        match SileroDetector::new() {
+            Ok(detector) => return (Box::new(detector), VadEngine::Silero),
+            Err(e) => tracing::warn!("Failed to load Silero VAD, falling back to energy VAD: {}", e),
+        }
     }
+    (Box::new(EnergyDetector::new()), VadEngine::Energy)
+}
+
+pub struct VadProcessor {
+    detector: Box<dyn VoiceDetector>,
+    /// Engine in use (differs from `settings.engine` after a fallback)
+    engine: VadEngine,
+    settings: VadSettings,
+}
+
+impl VadProcessor {
+    pub fn new(settings: &VadSettings) -> Self {
+        let (detector, engine) = create_detector(settings.engine);
+
+        tracing::info!(
+            "VAD initialized ({}): {}Hz, {} samples/chunk",
+            detector.name(),
+            SAMPLE_RATE,
+            CHUNK_SIZE
+        );
+
+        Self {
+            detector,
+            engine,
+            settings: settings.clone(),
+        }
+    }
+
+    /// Engine in use, which is `Energy` if the Silero model failed to load
+    pub fn engine(&self) -> VadEngine {
+        self.engine
+    }
+
+    /// Whether the configured engine could not be loaded and `Energy` is used instead
+    pub fn fell_back(&self) -> bool {
+        self.engine != self.settings.engine
+    }
+
+    /// Update the settings. The detector is only recreated when the engine changes,
+    /// so threshold, padding and ratio changes do not reload the model.
+    /// Returns true when the detector was recreated and fell back to `Energy`.
+    pub fn set_settings(&mut self, settings: &VadSettings) -> bool {
+        let recreated = settings.engine != self.settings.engine;
+        if recreated {
+            (self.detector, self.engine) = create_detector(settings.engine);
+            tracing::info!("VAD engine changed to {}", self.detector.name());
+        }
+        self.settings = settings.clone();
+        recreated && self.fell_back()
+    }
+
+    /// Speech probability of one `CHUNK_SIZE` chunk of 16kHz audio.
+    /// The detector keeps state between calls, so chunks must be passed in order.
+    pub fn speech_probability(&mut self, chunk: &[f32]) -> f32 {
+        self.detector.speech_probability(chunk)
+    }
+
+    /// Find the speech segments in 16kHz audio.
+    /// Returns a single segment covering all audio when most of it is speech, and
+    /// no segments when no speech is detected (prevents hallucination on silence).
+    pub fn detect_segments(&mut self, audio_data: &[f32]) -> Vec<SpeechSegment> {
+        let whole = || {
+            vec![SpeechSegment {
+                start: 0,
+                end: audio_data.len(),
+                samples: audio_data.to_vec(),
+            }]
+        };
 
-    /// Extract speech segments from audio data
-    /// Returns audio data with non-speech segments removed
-    pub fn extract_speech(&mut self, audio_data: &[f32]) -> Vec<f32> {
         if audio_data.len() < CHUNK_SIZE {
             tracing::warn!("Audio too short for VAD processing, returning as-is");
-            return audio_data.to_vec();
+            return whole();
         }
 
-        let mut speech_segments: Vec<f32> = Vec::new();
-        let mut total_chunks = 0;
-        let mut speech_chunks = 0;
-
-        // Use the label iterator to classify audio chunks
-        let labels = audio_data
-            .iter()
-            .copied()
-            .label(&mut self.detector, SPEECH_THRESHOLD, PADDING_CHUNKS);
-
-        for label in labels {
-            total_chunks += 1;
-            match label {
-                LabeledAudio::Speech(samples) => {
-                    speech_chunks += 1;
-                    speech_segments.extend(samples);
+        // Classify the audio chunk by chunk; the last partial chunk is zero-padded
+        self.detector.reset();
+        let probabilities: Vec<f32> = audio_data
+            .chunks(CHUNK_SIZE)
+            .map(|chunk| {
+                if chunk.len() == CHUNK_SIZE {
+                    self.detector.speech_probability(chunk)
+                } else {
+                    let mut padded = chunk.to_vec();
+                    padded.resize(CHUNK_SIZE, 0.0);
+                    self.detector.speech_probability(&padded)
                 }
-                LabeledAudio::NonSpeech(_) => {
-                    // Skip non-speech segments
-                }
-            }
-        }
+            })
+            .collect();
+        let labels = label_chunks(&probabilities, self.settings.threshold, self.settings.padding_chunks);
+        let (segments, total_chunks, speech_chunks) = collect_segments(audio_data, &labels);
 
-        // If most of the audio is speech (>80%), return original to preserve quality
-        if total_chunks > 0 && speech_chunks as f64 / total_chunks as f64 > 0.8 {
+        // If most of the audio is speech, return original to preserve quality
+        let keep_original_ratio = self.settings.keep_original_ratio as f64;
+        if total_chunks > 0 && speech_chunks as f64 / total_chunks as f64 > keep_original_ratio {
             tracing::info!(
-                "VAD: {}/{} chunks contain speech (>80%), using original audio",
+                "VAD: {}/{} chunks contain speech (>{:.0}%), using original audio",
                 speech_chunks,
-                total_chunks
+                total_chunks,
+                keep_original_ratio * 100.0
             );
-            return audio_data.to_vec();
+            return whole();
         }
 
-        // If no speech detected, return empty (prevents hallucination on silence)
-        if speech_segments.is_empty() {
+        if segments.is_empty() {
             tracing::info!("VAD: No speech detected in {} chunks", total_chunks);
-            return Vec::new();
+            return segments;
         }
 
         tracing::info!(
-            "VAD: {}/{} chunks contain speech ({:.1}%), output {} samples",
+            "VAD: {}/{} chunks contain speech ({:.1}%), {} segments",
             speech_chunks,
             total_chunks,
             if total_chunks > 0 { speech_chunks as f64 / total_chunks as f64 * 100.0 } else { 0.0 },
-            speech_segments.len()
+            segments.len()
         );
 
-        speech_segments
+        segments
     }
 }
 
@@ -85,19 +285,87 @@ impl VadProcessor {
 mod tests {
     use super::*;
 
+    fn segment(start: usize, len: usize) -> SpeechSegment {
+        SpeechSegment {
+            start,
+            end: start + len,
+            samples: vec![1.0; len],
+        }
+    }
+
     #[test]
     fn test_vad_creation() {
-        let vad = VadProcessor::new();
-        assert!(vad.is_ok());
+        let vad = VadProcessor::new(&VadSettings::default());
+        assert_eq!(vad.engine(), VadEngine::Silero);
+        assert!(!vad.fell_back());
+    }
+
+    #[test]
+    fn test_engine_switch() {
+        let mut vad = VadProcessor::new(&VadSettings {
+            engine: VadEngine::Energy,
+            ..VadSettings::default()
+        });
+        assert_eq!(vad.engine(), VadEngine::Energy);
+        assert!(!vad.set_settings(&VadSettings::default()));
+        assert_eq!(vad.engine(), VadEngine::Silero);
     }
 
     #[test]
     fn test_silence_detection() {
-        let mut vad = VadProcessor::new().unwrap();
+        let mut vad = VadProcessor::new(&VadSettings::default());
         // Create silence (zeros)
         let silence: Vec<f32> = vec![0.0; CHUNK_SIZE * 10];
-        let result = vad.extract_speech(&silence);
+        let segments = vad.detect_segments(&silence);
         // Should return empty or very little for silence
-        assert!(result.len() < silence.len());
+        let speech_len: usize = segments.iter().map(|s| s.samples.len()).sum();
+        assert!(speech_len < silence.len());
+    }
+
+    #[test]
+    fn test_label_chunks_with_padding() {
+        let probabilities = [0.1, 0.1, 0.1, 0.9, 0.1, 0.1, 0.1, 0.8];
+        assert_eq!(
+            label_chunks(&probabilities, 0.5, 1),
+            [false, false, true, true, true, false, true, true]
+        );
+        assert_eq!(label_chunks(&probabilities, 0.95, 3), [false; 8]);
+    }
+
+    #[test]
+    fn test_collect_segments() {
+        let audio: Vec<f32> = [
+            vec![0.0; CHUNK_SIZE],
+            vec![1.0; CHUNK_SIZE],
+            vec![2.0; CHUNK_SIZE],
+            vec![0.0; CHUNK_SIZE],
+            vec![3.0; 2],
+        ]
+        .concat();
+        let (segments, total, speech) = collect_segments(&audio, &[false, true, true, false, true]);
+
+        assert_eq!((total, speech), (5, 3));
+        assert_eq!(segments.len(), 2);
+        assert_eq!((segments[0].start, segments[0].end), (CHUNK_SIZE, CHUNK_SIZE * 3));
+        assert_eq!(segments[0].samples, [[1.0; CHUNK_SIZE], [2.0; CHUNK_SIZE]].concat());
+        assert_eq!((segments[1].start, segments[1].end), (CHUNK_SIZE * 4, CHUNK_SIZE * 4 + 2));
+    }
+
+    #[test]
+    fn test_join_segments_with_gap() {
+        let segments = [segment(100, 10), segment(500, 5)];
+        let joined = join_segments(&segments, 3);
+
+        assert_eq!(joined.samples.len(), 18);
+        assert_eq!(&joined.samples[10..13], &[0.0; 3]);
+        assert_eq!(joined.to_original(0), 100);
+        assert_eq!(joined.to_original(9), 109);
+        // Inside the inserted silence
+        assert_eq!(joined.to_original(11), 110);
+        assert_eq!(joined.to_original(13), 500);
+        assert_eq!(joined.to_original(17), 504);
+        assert_eq!(joined.gap_boundaries(), vec![11]);
+
+        assert!(join_segments(&[], 3).samples.is_empty());
     }
 }
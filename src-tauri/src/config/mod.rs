@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 
@@ -14,6 +15,15 @@ pub struct Settings {
     /// Shortcut key settings
     #[serde(default)]
     pub shortcut: ShortcutSettings,
+    /// Audio input settings
+    #[serde(default)]
+    pub audio: AudioSettings,
+    /// Voice activity detection settings
+    #[serde(default)]
+    pub vad: VadSettings,
+    /// Voice-activated recording settings
+    #[serde(default)]
+    pub hands_free: HandsFreeSettings,
     /// Whether settings were loaded from a saved file (not defaults)
     #[serde(skip_deserializing, default)]
     pub is_saved: bool,
@@ -28,12 +38,305 @@ pub struct WhisperSettings {
     /// Maximum recording time in seconds (default: 300 = 5 minutes)
     #[serde(default = "default_max_recording_seconds")]
     pub max_recording_seconds: u32,
+    /// Stop recording after this much silence following speech (0 = disabled)
+    #[serde(default)]
+    pub auto_stop_silence_ms: u32,
+    /// Spoken language code (e.g. "ja", "en") or "auto" to let Whisper detect it
+    #[serde(default = "default_language")]
+    pub language: String,
+    /// Translate the transcription to English
+    #[serde(default)]
+    pub translate: bool,
+    /// Initial prompt and vocabulary that bias the decoder
+    #[serde(default)]
+    pub prompt: WhisperPrompt,
+    /// Prompts used instead of `prompt` while a prompt preset is selected
+    #[serde(default)]
+    pub preset_prompts: HashMap<PromptPreset, WhisperPrompt>,
+    /// Decoder parameters (latency/accuracy trade-off)
+    #[serde(default)]
+    pub decoding: DecodingSettings,
+    /// Run Whisper on the GPU when the app is built with a GPU backend
+    #[serde(default = "default_use_gpu")]
+    pub use_gpu: bool,
+    /// Index of the GPU used by Whisper
+    #[serde(default)]
+    pub gpu_device: u32,
+    /// Partial results while recording
+    #[serde(default)]
+    pub live: LiveTranscriptionSettings,
+    /// Removal of hallucinated and repeated text
+    #[serde(default)]
+    pub filter: OutputFilterSettings,
+}
+
+/// Text passed to Whisper as the initial prompt, so product names and jargon
+/// are recognized with the expected spelling
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct WhisperPrompt {
+    /// Free text, e.g. a sample sentence in the expected style
+    #[serde(default)]
+    pub initial_prompt: String,
+    /// Words to bias recognition towards
+    #[serde(default)]
+    pub vocabulary: Vec<String>,
 }
 
+/// Upper bound for `WhisperPrompt::initial_prompt` (Whisper only reads the last ~224 tokens)
+pub const MAX_INITIAL_PROMPT_CHARS: usize = 1000;
+
+/// Upper bound for `WhisperPrompt::vocabulary`
+pub const MAX_VOCABULARY_WORDS: usize = 200;
+
+impl WhisperPrompt {
+    pub fn is_empty(&self) -> bool {
+        self.initial_prompt.is_empty() && self.vocabulary.is_empty()
+    }
+
+    /// Trim whitespace, drop empty words and cap the length (hand-edited config files)
+    pub fn validate(&mut self) {
+        self.initial_prompt = self.initial_prompt.trim().to_string();
+        if let Some((index, _)) = self.initial_prompt.char_indices().nth(MAX_INITIAL_PROMPT_CHARS) {
+            tracing::warn!("Initial prompt is too long, keeping {} characters", MAX_INITIAL_PROMPT_CHARS);
+            self.initial_prompt.truncate(index);
+        }
+        self.vocabulary = self
+            .vocabulary
+            .iter()
+            .map(|word| word.trim().to_string())
+            .filter(|word| !word.is_empty())
+            .collect();
+        if self.vocabulary.len() > MAX_VOCABULARY_WORDS {
+            tracing::warn!("Vocabulary is too long, keeping {} words", MAX_VOCABULARY_WORDS);
+            self.vocabulary.truncate(MAX_VOCABULARY_WORDS);
+        }
+    }
+}
+
+/// `WhisperSettings::language` value that enables language detection
+pub const AUTO_LANGUAGE: &str = "auto";
+
 fn default_insert_newline() -> bool {
     true
 }
 
+fn default_language() -> String {
+    "ja".to_string()
+}
+
+fn default_use_gpu() -> bool {
+    true
+}
+
+/// Post-transcription filter against Whisper hallucinations: known phrases
+/// produced from silence, runaway repetition and segments without speech
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct OutputFilterSettings {
+    pub enabled: bool,
+    /// Segments consisting of one of these phrases are removed. Punctuation, spaces
+    /// and case are ignored; a trailing `*` also matches any continuation.
+    pub blocklist: Vec<String>,
+    /// Phrases repeated more often than this in a row are cut back to this count (0 = off)
+    pub max_repeats: u32,
+    /// Segments with a no-speech probability above this are removed (1.0 = off)
+    pub no_speech_prob: f32,
+}
+
+/// Upper bound for `OutputFilterSettings::blocklist`
+pub const MAX_BLOCKLIST_PHRASES: usize = 200;
+
+/// Phrases Whisper is known to produce from silence (subtitle credits of its training data)
+pub fn default_hallucination_phrases() -> Vec<String> {
+    [
+        "ご視聴ありがとうございました",
+        "ご視聴いただきありがとうございました",
+        "チャンネル登録よろしくお願いします",
+        "チャンネル登録お願いします",
+        "字幕by*",
+        "字幕作成*",
+        "Thank you for watching",
+        "Thanks for watching",
+        "Subtitles by*",
+    ]
+    .iter()
+    .map(|phrase| phrase.to_string())
+    .collect()
+}
+
+impl Default for OutputFilterSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            blocklist: default_hallucination_phrases(),
+            max_repeats: 3,
+            no_speech_prob: 0.8,
+        }
+    }
+}
+
+impl OutputFilterSettings {
+    /// Trim phrases, drop empty ones and fix out-of-range values (hand-edited config files)
+    pub fn validate(&mut self) {
+        self.blocklist = self
+            .blocklist
+            .iter()
+            .map(|phrase| phrase.trim().to_string())
+            .filter(|phrase| !phrase.is_empty())
+            .collect();
+        if self.blocklist.len() > MAX_BLOCKLIST_PHRASES {
+            tracing::warn!("Blocklist is too long, keeping {} phrases", MAX_BLOCKLIST_PHRASES);
+            self.blocklist.truncate(MAX_BLOCKLIST_PHRASES);
+        }
+        self.no_speech_prob = valid_fraction(
+            "no-speech probability filter",
+            self.no_speech_prob,
+            Self::default().no_speech_prob,
+        );
+    }
+}
+
+/// Live transcription: the growing recording is transcribed periodically and
+/// partial results are shown while recording
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct LiveTranscriptionSettings {
+    pub enabled: bool,
+    /// Time between transcriptions of the window
+    pub interval_ms: u32,
+    /// Window length after which it slides past the confirmed text
+    pub window_secs: u32,
+}
+
+/// Allowed range for `LiveTranscriptionSettings::interval_ms`
+pub const LIVE_INTERVAL_MS_RANGE: std::ops::RangeInclusive<u32> = 300..=10_000;
+
+/// Allowed range for `LiveTranscriptionSettings::window_secs` (Whisper reads 30 seconds at most)
+pub const LIVE_WINDOW_SECS_RANGE: std::ops::RangeInclusive<u32> = 5..=25;
+
+impl Default for LiveTranscriptionSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_ms: 1000,
+            window_secs: 15,
+        }
+    }
+}
+
+impl LiveTranscriptionSettings {
+    /// Clamp out-of-range values (hand-edited config files)
+    pub fn validate(&mut self) {
+        self.interval_ms = self
+            .interval_ms
+            .clamp(*LIVE_INTERVAL_MS_RANGE.start(), *LIVE_INTERVAL_MS_RANGE.end());
+        self.window_secs = self
+            .window_secs
+            .clamp(*LIVE_WINDOW_SECS_RANGE.start(), *LIVE_WINDOW_SECS_RANGE.end());
+    }
+}
+
+/// Whisper decoder parameters. The defaults match whisper.cpp except for the
+/// greedy single-candidate search, which keeps latency low.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct DecodingSettings {
+    /// Beam search width (0 or 1 = greedy decoding)
+    pub beam_size: u32,
+    /// Candidates sampled per temperature in greedy decoding
+    pub best_of: u32,
+    /// Initial sampling temperature (0.0 = deterministic)
+    pub temperature: f32,
+    /// Temperature increase when a segment fails the thresholds (0.0 = no fallback)
+    pub temperature_inc: f32,
+    /// Segments whose no-speech probability exceeds this (and fail the logprob
+    /// threshold) are treated as silence
+    pub no_speech_thold: f32,
+    /// Average token log probability below which decoding is retried at a higher temperature
+    pub logprob_thold: f32,
+    /// CPU threads used by Whisper (0 = whisper-rs default)
+    pub n_threads: u32,
+    /// Suppress blank outputs at the start of a segment
+    pub suppress_blank: bool,
+    /// Suppress non-speech tokens (music symbols, brackets, ...)
+    pub suppress_non_speech: bool,
+}
+
+/// Upper bound for the beam size and best_of (whisper.cpp's decoder limit)
+pub const MAX_DECODERS: u32 = 8;
+
+/// Allowed range for `DecodingSettings::logprob_thold`
+pub const LOGPROB_THOLD_RANGE: std::ops::RangeInclusive<f32> = -5.0..=0.0;
+
+/// Upper bound for `DecodingSettings::n_threads`
+pub const MAX_WHISPER_THREADS: u32 = 64;
+
+impl Default for DecodingSettings {
+    fn default() -> Self {
+        Self {
+            beam_size: 0,
+            best_of: 1,
+            temperature: 0.0,
+            temperature_inc: 0.2,
+            no_speech_thold: 0.6,
+            logprob_thold: -1.0,
+            n_threads: 0,
+            suppress_blank: true,
+            suppress_non_speech: false,
+        }
+    }
+}
+
+impl DecodingSettings {
+    /// Replace out-of-range values (hand-edited config files) with the nearest valid value
+    pub fn validate(&mut self) {
+        let defaults = Self::default();
+        self.beam_size = self.beam_size.min(MAX_DECODERS);
+        self.best_of = self.best_of.clamp(1, MAX_DECODERS);
+        self.temperature = valid_fraction("temperature", self.temperature, defaults.temperature);
+        self.temperature_inc = valid_fraction(
+            "temperature increment",
+            self.temperature_inc,
+            defaults.temperature_inc,
+        );
+        self.no_speech_thold = valid_fraction(
+            "no-speech threshold",
+            self.no_speech_thold,
+            defaults.no_speech_thold,
+        );
+        self.logprob_thold = if self.logprob_thold.is_finite() {
+            self.logprob_thold
+                .clamp(*LOGPROB_THOLD_RANGE.start(), *LOGPROB_THOLD_RANGE.end())
+        } else {
+            defaults.logprob_thold
+        };
+        if self.n_threads > MAX_WHISPER_THREADS {
+            tracing::warn!("{} Whisper threads requested, using {}", self.n_threads, MAX_WHISPER_THREADS);
+            self.n_threads = MAX_WHISPER_THREADS;
+        }
+    }
+}
+
+impl WhisperSettings {
+    /// Normalize the language code and prompts (hand-edited config files)
+    pub fn validate(&mut self) {
+        let language = self.language.trim().to_lowercase();
+        self.language = if language.is_empty() { AUTO_LANGUAGE.to_string() } else { language };
+
+        self.prompt.validate();
+        self.preset_prompts.values_mut().for_each(WhisperPrompt::validate);
+        self.preset_prompts.retain(|_, prompt| !prompt.is_empty());
+        self.decoding.validate();
+        self.live.validate();
+        self.filter.validate();
+    }
+
+    /// Prompt for the selected prompt preset: its override if set, otherwise the common prompt
+    pub fn prompt_for(&self, preset: &PromptPreset) -> &WhisperPrompt {
+        self.preset_prompts.get(preset).unwrap_or(&self.prompt)
+    }
+}
+
 fn default_max_recording_seconds() -> u32 {
     300 // 5 minutes
 }
@@ -43,16 +346,273 @@ fn default_max_recording_seconds() -> u32 {
 pub struct ShortcutSettings {
     /// The shortcut key combination (e.g., "Ctrl+Space", "Alt+R")
     pub recording_toggle: String,
+    /// Cancels the transcription or LLM refinement in progress (empty = no shortcut).
+    /// Off by default, so upgrading does not take a key combination away from other apps
+    #[serde(default)]
+    pub cancel: String,
 }
 
 impl Default for ShortcutSettings {
     fn default() -> Self {
         Self {
             recording_toggle: "Ctrl+Space".to_string(),
+            cancel: String::new(),
+        }
+    }
+}
+
+/// Audio input settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AudioSettings {
+    /// Input device name (None = OS default device)
+    #[serde(default)]
+    pub input_device: Option<String>,
+    /// Resume on the next available device when the recording device is unplugged
+    #[serde(default = "default_reconnect_on_device_lost")]
+    pub reconnect_on_device_lost: bool,
+    /// Keep the microphone open between recordings so the pre-roll can be captured
+    #[serde(default)]
+    pub always_warm: bool,
+    /// Audio before the shortcut press included in the recording (always-warm mode only)
+    #[serde(default = "default_preroll_ms")]
+    pub preroll_ms: u32,
+    /// How the device channels are mixed down to mono
+    #[serde(default)]
+    pub channel_mix: ChannelMix,
+    /// Save each recording as a WAV file linked from its log entry
+    #[serde(default)]
+    pub archive_recordings: bool,
+    /// Which audio is written to the archive
+    #[serde(default)]
+    pub archive_source: ArchiveSource,
+    /// Speed/quality trade-off of the resampling to 16 kHz
+    #[serde(default)]
+    pub resample_quality: ResampleQuality,
+}
+
+fn default_reconnect_on_device_lost() -> bool {
+    true
+}
+
+fn default_preroll_ms() -> u32 {
+    500
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            input_device: None,
+            reconnect_on_device_lost: true,
+            always_warm: false,
+            preroll_ms: default_preroll_ms(),
+            channel_mix: ChannelMix::default(),
+            archive_recordings: false,
+            archive_source: ArchiveSource::default(),
+            resample_quality: ResampleQuality::default(),
+        }
+    }
+}
+
+/// Audio written to the recording archive
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+pub enum ArchiveSource {
+    /// Mono audio at the device sample rate
+    Captured,
+    /// 16 kHz audio as passed to Whisper (smaller files)
+    #[default]
+    Resampled,
+}
+
+/// Voice activity detection settings (applied when a recording is processed)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct VadSettings {
+    /// Remove non-speech parts before transcription
+    #[serde(default = "default_vad_enabled")]
+    pub enabled: bool,
+    /// Speech detector (Silero falls back to Energy when the model cannot be loaded)
+    #[serde(default)]
+    pub engine: VadEngine,
+    /// Speech probability (0.0-1.0) above which a chunk counts as speech.
+    /// Lower values keep soft-spoken words in noisy rooms.
+    #[serde(default = "default_vad_threshold")]
+    pub threshold: f32,
+    /// Chunks (32ms each) kept before and after each speech segment
+    #[serde(default = "default_vad_padding_chunks")]
+    pub padding_chunks: usize,
+    /// If more than this fraction of chunks is speech, the original audio is used as-is
+    #[serde(default = "default_vad_keep_original_ratio")]
+    pub keep_original_ratio: f32,
+    /// Silence inserted between speech segments when they are joined (0 = none)
+    #[serde(default = "default_vad_segment_gap_ms")]
+    pub segment_gap_ms: u32,
+}
+
+/// Speech detector used by the VAD
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+pub enum VadEngine {
+    /// Silero neural network model
+    #[default]
+    Silero,
+    /// Signal energy and zero-crossing rate (no model, less accurate in noise)
+    Energy,
+}
+
+/// Upper bound for `VadSettings::padding_chunks` (~1 second)
+pub const MAX_VAD_PADDING_CHUNKS: usize = 31;
+
+/// Upper bound for `VadSettings::segment_gap_ms`
+pub const MAX_VAD_SEGMENT_GAP_MS: u32 = 2000;
+
+fn default_vad_enabled() -> bool {
+    true
+}
+
+fn default_vad_threshold() -> f32 {
+    0.5
+}
+
+fn default_vad_padding_chunks() -> usize {
+    3
+}
+
+fn default_vad_keep_original_ratio() -> f32 {
+    0.8
+}
+
+fn default_vad_segment_gap_ms() -> u32 {
+    300
+}
+
+impl Default for VadSettings {
+    fn default() -> Self {
+        Self {
+            enabled: default_vad_enabled(),
+            engine: VadEngine::default(),
+            threshold: default_vad_threshold(),
+            padding_chunks: default_vad_padding_chunks(),
+            keep_original_ratio: default_vad_keep_original_ratio(),
+            segment_gap_ms: default_vad_segment_gap_ms(),
+        }
+    }
+}
+
+impl VadSettings {
+    /// Replace out-of-range values (hand-edited config files) with the nearest valid value
+    pub fn validate(&mut self) {
+        self.threshold = valid_fraction("VAD threshold", self.threshold, default_vad_threshold());
+        self.keep_original_ratio = valid_fraction(
+            "VAD keep-original ratio",
+            self.keep_original_ratio,
+            default_vad_keep_original_ratio(),
+        );
+        if self.padding_chunks > MAX_VAD_PADDING_CHUNKS {
+            tracing::warn!(
+                "VAD padding of {} chunks is too long, using {}",
+                self.padding_chunks,
+                MAX_VAD_PADDING_CHUNKS
+            );
+            self.padding_chunks = MAX_VAD_PADDING_CHUNKS;
+        }
+        if self.segment_gap_ms > MAX_VAD_SEGMENT_GAP_MS {
+            tracing::warn!(
+                "VAD segment gap of {}ms is too long, using {}ms",
+                self.segment_gap_ms,
+                MAX_VAD_SEGMENT_GAP_MS
+            );
+            self.segment_gap_ms = MAX_VAD_SEGMENT_GAP_MS;
+        }
+    }
+}
+
+/// Clamp a value to 0.0-1.0 (NaN/infinity fall back to `default`)
+fn valid_fraction(name: &str, value: f32, default: f32) -> f32 {
+    let fixed = if value.is_finite() { value.clamp(0.0, 1.0) } else { default };
+    if fixed != value {
+        tracing::warn!("Invalid {} {}, using {}", name, value, fixed);
+    }
+    fixed
+}
+
+/// Hands-free mode: the microphone stays open and every utterance detected by the
+/// VAD is transcribed and output automatically
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HandsFreeSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Silence that ends an utterance
+    #[serde(default = "default_silence_timeout_ms")]
+    pub silence_timeout_ms: u32,
+    /// Utterances with less speech than this are ignored (coughs, clicks)
+    #[serde(default = "default_min_speech_ms")]
+    pub min_speech_ms: u32,
+}
+
+/// Allowed range for `HandsFreeSettings::silence_timeout_ms`
+pub const SILENCE_TIMEOUT_RANGE_MS: std::ops::RangeInclusive<u32> = 200..=10_000;
+
+/// Upper bound for `HandsFreeSettings::min_speech_ms`
+pub const MAX_MIN_SPEECH_MS: u32 = 5_000;
+
+fn default_silence_timeout_ms() -> u32 {
+    800
+}
+
+fn default_min_speech_ms() -> u32 {
+    250
+}
+
+impl Default for HandsFreeSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            silence_timeout_ms: default_silence_timeout_ms(),
+            min_speech_ms: default_min_speech_ms(),
+        }
+    }
+}
+
+impl HandsFreeSettings {
+    /// Clamp out-of-range values (hand-edited config files)
+    pub fn validate(&mut self) {
+        let timeout = self
+            .silence_timeout_ms
+            .clamp(*SILENCE_TIMEOUT_RANGE_MS.start(), *SILENCE_TIMEOUT_RANGE_MS.end());
+        if timeout != self.silence_timeout_ms {
+            tracing::warn!("Invalid silence timeout {}ms, using {}ms", self.silence_timeout_ms, timeout);
+            self.silence_timeout_ms = timeout;
+        }
+        if self.min_speech_ms > MAX_MIN_SPEECH_MS {
+            tracing::warn!("Minimum speech of {}ms is too long, using {}ms", self.min_speech_ms, MAX_MIN_SPEECH_MS);
+            self.min_speech_ms = MAX_MIN_SPEECH_MS;
         }
     }
 }
 
+/// Resampling quality preset
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+pub enum ResampleQuality {
+    /// Cubic polynomial interpolation (fastest, no anti-aliasing filter)
+    Fast,
+    /// FFT-based resampling
+    Balanced,
+    /// Windowed sinc interpolation (slowest)
+    #[default]
+    Best,
+}
+
+/// Mono downmix of a multi-channel input device
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(tag = "type")]
+pub enum ChannelMix {
+    /// Average all channels
+    #[default]
+    Average,
+    /// Use a single channel (0-based index)
+    Single { channel: usize },
+    /// Weighted sum of the channels (one gain per channel, missing gains are 0)
+    Custom { gains: Vec<f32> },
+}
+
 /// Output mode for transcription results
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum OutputMode {
@@ -87,7 +647,7 @@ impl Default for LlmProvider {
 
 
 /// Available prompt presets
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum PromptPreset {
     /// Default: Natural Japanese text formatting
     Default,
@@ -215,10 +775,23 @@ impl Default for Settings {
                 model_name: "large-v3-turbo".to_string(),
                 insert_newline: true,
                 max_recording_seconds: 300,
+                auto_stop_silence_ms: 0,
+                language: default_language(),
+                translate: false,
+                prompt: WhisperPrompt::default(),
+                preset_prompts: HashMap::new(),
+                decoding: DecodingSettings::default(),
+                use_gpu: default_use_gpu(),
+                gpu_device: 0,
+                live: LiveTranscriptionSettings::default(),
+                filter: OutputFilterSettings::default(),
             },
             llm: LlmSettings::default(),
             output_mode: OutputMode::default(),
             shortcut: ShortcutSettings::default(),
+            audio: AudioSettings::default(),
+            vad: VadSettings::default(),
+            hands_free: HandsFreeSettings::default(),
             is_saved: false,
         }
     }
@@ -245,6 +818,9 @@ pub fn load_settings() -> Settings {
         Ok(content) => match serde_json::from_str::<Settings>(&content) {
             Ok(mut settings) => {
                 tracing::info!("Loaded settings from {:?}", config_path);
+                settings.whisper.validate();
+                settings.vad.validate();
+                settings.hands_free.validate();
                 settings.is_saved = true;
                 settings
             }
@@ -277,3 +853,182 @@ pub fn save_settings(settings: &Settings) -> Result<(), String> {
     tracing::info!("Saved settings to {:?}", config_path);
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vad_settings_defaults_when_missing() {
+        let settings: Settings = serde_json::from_str(r#"{"whisper": {"model_name": "base"}}"#).unwrap();
+        assert_eq!(settings.vad, VadSettings::default());
+    }
+
+    #[test]
+    fn test_whisper_language() {
+        let settings: Settings = serde_json::from_str(r#"{"whisper": {"model_name": "base"}}"#).unwrap();
+        assert_eq!(settings.whisper.language, "ja");
+        assert!(!settings.whisper.translate);
+
+        let mut whisper = WhisperSettings {
+            language: " EN ".to_string(),
+            ..settings.whisper.clone()
+        };
+        whisper.validate();
+        assert_eq!(whisper.language, "en");
+
+        whisper.language = String::new();
+        whisper.validate();
+        assert_eq!(whisper.language, AUTO_LANGUAGE);
+    }
+
+    #[test]
+    fn test_output_filter_validate() {
+        let settings: Settings = serde_json::from_str(r#"{"whisper": {"model_name": "base"}}"#).unwrap();
+        assert_eq!(settings.whisper.filter, OutputFilterSettings::default());
+
+        let mut filter = OutputFilterSettings {
+            blocklist: vec![" 字幕by* ".to_string(), "  ".to_string()],
+            no_speech_prob: f32::NAN,
+            ..OutputFilterSettings::default()
+        };
+        filter.validate();
+        assert_eq!(filter.blocklist, vec!["字幕by*"]);
+        assert_eq!(filter.no_speech_prob, 0.8);
+    }
+
+    #[test]
+    fn test_live_transcription_validate() {
+        let mut live = LiveTranscriptionSettings {
+            enabled: true,
+            interval_ms: 0,
+            window_secs: 60,
+        };
+        live.validate();
+        assert_eq!(live.interval_ms, *LIVE_INTERVAL_MS_RANGE.start());
+        assert_eq!(live.window_secs, *LIVE_WINDOW_SECS_RANGE.end());
+    }
+
+    #[test]
+    fn test_cancel_shortcut_off_when_missing() {
+        let settings: Settings = serde_json::from_str(r#"{"whisper": {"model_name": "base"}, "shortcut": {"recording_toggle": "Alt+R"}}"#).unwrap();
+        assert_eq!(settings.shortcut.cancel, "");
+        assert_eq!(Settings::default().shortcut.cancel, "");
+    }
+
+    #[test]
+    fn test_whisper_gpu_defaults_when_missing() {
+        let settings: Settings = serde_json::from_str(r#"{"whisper": {"model_name": "base"}}"#).unwrap();
+        assert!(settings.whisper.use_gpu);
+        assert_eq!(settings.whisper.gpu_device, 0);
+    }
+
+    #[test]
+    fn test_whisper_prompt_for_preset() {
+        let mut settings = Settings::default();
+        settings.whisper.prompt.vocabulary = vec![" HushWhisper ".to_string(), "  ".to_string()];
+        settings.whisper.preset_prompts.insert(
+            PromptPreset::Meeting,
+            WhisperPrompt {
+                initial_prompt: "定例会議の議事録です。".to_string(),
+                vocabulary: vec![],
+            },
+        );
+        settings.whisper.preset_prompts.insert(PromptPreset::Chat, WhisperPrompt::default());
+        settings.whisper.validate();
+
+        assert_eq!(settings.whisper.prompt.vocabulary, ["HushWhisper"]);
+        // Empty overrides are removed so the common prompt applies
+        assert!(!settings.whisper.preset_prompts.contains_key(&PromptPreset::Chat));
+        assert_eq!(settings.whisper.prompt_for(&PromptPreset::Chat), &settings.whisper.prompt);
+        assert_eq!(
+            settings.whisper.prompt_for(&PromptPreset::Meeting).initial_prompt,
+            "定例会議の議事録です。"
+        );
+
+        let json = serde_json::to_string(&settings.whisper).unwrap();
+        let restored: WhisperSettings = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.preset_prompts, settings.whisper.preset_prompts);
+    }
+
+    #[test]
+    fn test_whisper_prompt_length_limit() {
+        let mut prompt = WhisperPrompt {
+            initial_prompt: "あ".repeat(MAX_INITIAL_PROMPT_CHARS + 10),
+            vocabulary: vec!["word".to_string(); MAX_VOCABULARY_WORDS + 1],
+        };
+        prompt.validate();
+        assert_eq!(prompt.initial_prompt.chars().count(), MAX_INITIAL_PROMPT_CHARS);
+        assert_eq!(prompt.vocabulary.len(), MAX_VOCABULARY_WORDS);
+    }
+
+    #[test]
+    fn test_decoding_settings_validate() {
+        let settings: Settings = serde_json::from_str(
+            r#"{"whisper": {"model_name": "base", "decoding": {"beam_size": 5}}}"#,
+        )
+        .unwrap();
+        // Missing fields keep their defaults
+        assert_eq!(
+            settings.whisper.decoding,
+            DecodingSettings {
+                beam_size: 5,
+                ..DecodingSettings::default()
+            }
+        );
+
+        let mut decoding = DecodingSettings {
+            beam_size: 20,
+            best_of: 0,
+            temperature: -1.0,
+            temperature_inc: f32::NAN,
+            no_speech_thold: 2.0,
+            logprob_thold: -10.0,
+            n_threads: 1000,
+            ..DecodingSettings::default()
+        };
+        decoding.validate();
+        assert_eq!((decoding.beam_size, decoding.best_of), (MAX_DECODERS, 1));
+        assert_eq!(decoding.temperature, 0.0);
+        assert_eq!(decoding.temperature_inc, 0.2);
+        assert_eq!(decoding.no_speech_thold, 1.0);
+        assert_eq!(decoding.logprob_thold, -5.0);
+        assert_eq!(decoding.n_threads, MAX_WHISPER_THREADS);
+    }
+
+    #[test]
+    fn test_vad_settings_validate() {
+        let mut vad = VadSettings {
+            enabled: true,
+            engine: VadEngine::Energy,
+            threshold: 1.5,
+            padding_chunks: 100,
+            keep_original_ratio: f32::NAN,
+            segment_gap_ms: 60_000,
+        };
+        vad.validate();
+        assert_eq!(vad.threshold, 1.0);
+        assert_eq!(vad.padding_chunks, MAX_VAD_PADDING_CHUNKS);
+        assert_eq!(vad.keep_original_ratio, 0.8);
+        assert_eq!(vad.segment_gap_ms, MAX_VAD_SEGMENT_GAP_MS);
+
+        let mut valid = VadSettings {
+            threshold: 0.3,
+            ..VadSettings::default()
+        };
+        valid.validate();
+        assert_eq!(valid.threshold, 0.3);
+    }
+
+    #[test]
+    fn test_hands_free_settings_validate() {
+        let mut hands_free = HandsFreeSettings {
+            enabled: true,
+            silence_timeout_ms: 10,
+            min_speech_ms: 60_000,
+        };
+        hands_free.validate();
+        assert_eq!(hands_free.silence_timeout_ms, *SILENCE_TIMEOUT_RANGE_MS.start());
+        assert_eq!(hands_free.min_speech_ms, MAX_MIN_SPEECH_MS);
+    }
+}
@@ -7,19 +7,40 @@ mod shortcuts;
 mod tray;
 mod whisper;
 
-use audio::{AudioCapture, Resampler, VadProcessor};
+use audio::utterance::{SilenceMonitor, StreamingVad, TrailingSilence, UtteranceConfig, UtteranceEvent};
+use audio::resample::StreamingResampler;
+use audio::{AudioCapture, DeviceLostCallback, LevelMonitor, Resampler, VadProcessor};
 use clipboard::ClipboardManager;
 use llm::LlmClient;
 use shortcuts::ShortcutHandler;
 use tray::TrayManager;
-use whisper::WhisperTranscriber;
+use whisper::{LiveTranscript, WhisperTranscriber};
 
 use futures_util::StreamExt;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use tauri::{AppHandle, Emitter, Manager, State, WindowEvent};
 use tokio::io::AsyncWriteExt;
 
+/// Interval between `audio-level` events while recording
+const LEVEL_EVENT_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+
+/// How often the hands-free listener reads captured audio
+const HANDS_FREE_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(50);
+
+/// Speech required before trailing silence can stop a recording
+const AUTO_STOP_MIN_SPEECH_MS: u32 = 250;
+
+/// Audio the live transcription waits for before the first pass (16kHz samples)
+const LIVE_MIN_WINDOW_SAMPLES: usize = 16000;
+
+/// Error returned by `process_audio` when `cancel_transcription` stopped it
+const TRANSCRIPTION_CANCELLED: &str = "Transcription cancelled";
+
+/// Jobs that can wait for the transcription worker behind the running one
+const MAX_QUEUED_JOBS: usize = 8;
+
 /// Available Whisper models with their URLs and filenames
 const MODELS: &[(&str, &str, &str)] = &[
     ("large-v3-turbo-q8_0", "ggml-large-v3-turbo-q8_0.bin", "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-large-v3-turbo-q8_0.bin"),
@@ -62,9 +83,8 @@ fn expand_env_vars(path: &str) -> String {
 
 // Application state
 pub struct AppState {
+    // AudioCapture owns the cpal stream, which is not Send
     audio_capture: Mutex<Option<AudioCapture>>,
-    // Stream is not stored here because it's not Send
-    active_stream: Mutex<Option<Box<cpal::Stream>>>,
     whisper: Mutex<Option<WhisperTranscriber>>,
     clipboard: Mutex<Option<ClipboardManager>>,
     vad: Mutex<Option<VadProcessor>>,
@@ -72,6 +92,93 @@ pub struct AppState {
     current_shortcut: Mutex<String>,
     /// Handle for the auto-stop timer task
     auto_stop_handle: Mutex<Option<tauri::async_runtime::JoinHandle<()>>>,
+    /// Handle for the input level metering task
+    level_monitor_handle: Mutex<Option<tauri::async_runtime::JoinHandle<()>>>,
+    /// Running hands-free listener. An async lock, held while `apply_hands_free`
+    /// waits for the old listener, so switching listeners never interleaves.
+    hands_free: tokio::sync::Mutex<Option<HandsFreeListener>>,
+    /// Stop flag of the trailing-silence monitor thread
+    silence_monitor: Mutex<Option<Arc<AtomicBool>>>,
+    /// Running live transcription of the current recording
+    live_transcription: Mutex<Option<LiveTranscription>>,
+    /// Transcription worker thread and its jobs, started with the first job
+    transcription_queue: Mutex<Option<TranscriptionQueue>>,
+}
+
+/// Background thread that transcribes the recording while it grows
+struct LiveTranscription {
+    stop: Arc<AtomicBool>,
+    thread: std::thread::JoinHandle<LiveTranscript>,
+}
+
+/// Cancellation of a transcription job, requested with `cancel_transcription`
+#[derive(Clone, Default)]
+struct CancelToken {
+    /// Also read by Whisper's abort callback
+    flag: Arc<AtomicBool>,
+    notify: Arc<tokio::sync::Notify>,
+}
+
+impl CancelToken {
+    fn cancel(&self) {
+        self.flag.store(true, Ordering::Release);
+        // Leaves a permit, so a later `cancelled()` returns at once
+        self.notify.notify_one();
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.flag.load(Ordering::Acquire)
+    }
+
+    /// Completes once the run is cancelled
+    async fn cancelled(&self) {
+        if !self.is_cancelled() {
+            self.notify.notified().await;
+        }
+    }
+}
+
+/// Worker thread that runs transcription jobs one at a time, in the order they were queued
+struct TranscriptionQueue {
+    sender: std::sync::mpsc::SyncSender<TranscriptionJob>,
+    next_id: u64,
+    /// Queued and running jobs, the running one first
+    jobs: Vec<(u64, CancelToken)>,
+}
+
+/// Audio waiting for `run_pipeline` on the transcription worker
+struct TranscriptionJob {
+    id: u64,
+    audio_data: Vec<f32>,
+    sample_rate: u32,
+    source: AudioSource,
+    live: Option<LiveTranscript>,
+    cancel: CancelToken,
+    reply: tokio::sync::oneshot::Sender<Result<String, String>>,
+}
+
+/// State of a transcription job, emitted with `transcription-job`
+#[derive(Debug, Clone, Copy, serde::Serialize)]
+enum JobStatus {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Clone, serde::Serialize)]
+struct TranscriptionJobEvent {
+    id: u64,
+    status: JobStatus,
+    /// Jobs queued or running after this change
+    pending: usize,
+}
+
+/// Background thread that detects utterances on the open capture stream
+struct HandsFreeListener {
+    stop: Arc<AtomicBool>,
+    thread: std::thread::JoinHandle<()>,
 }
 
 // Manual Send/Sync implementation
@@ -84,13 +191,17 @@ impl AppState {
         let settings = config::load_settings();
         Self {
             audio_capture: Mutex::new(None),
-            active_stream: Mutex::new(None),
             whisper: Mutex::new(None),
             clipboard: Mutex::new(None),
             vad: Mutex::new(None),
             is_recording: Mutex::new(false),
             current_shortcut: Mutex::new(settings.shortcut.recording_toggle),
             auto_stop_handle: Mutex::new(None),
+            level_monitor_handle: Mutex::new(None),
+            hands_free: tokio::sync::Mutex::new(None),
+            silence_monitor: Mutex::new(None),
+            live_transcription: Mutex::new(None),
+            transcription_queue: Mutex::new(None),
         }
     }
 }
@@ -199,7 +310,7 @@ async fn initialize_whisper(
         if whisper_guard.is_some() {
             tracing::info!("Unloading previous model to free VRAM");
             *whisper_guard = None;
-            // Give CUDA time to clean up
+            // Give the GPU backend time to clean up
             drop(whisper_guard);
             std::thread::sleep(std::time::Duration::from_millis(100));
         }
@@ -229,13 +340,23 @@ async fn initialize_whisper(
             .map_err(|e| format!("Failed to emit event: {}", e))?;
     }
 
-    let transcriber = WhisperTranscriber::new(path)
+    let settings = config::load_settings();
+    let transcriber = WhisperTranscriber::new(path, &settings.whisper)
         .map_err(|e| format!("Failed to initialize Whisper: {}", e))?;
+    let backend = transcriber.backend();
 
     *state.whisper.lock().unwrap() = Some(transcriber);
 
-    tracing::info!("Whisper initialized successfully");
-    Ok("Whisper initialized successfully".to_string())
+    tracing::info!("Whisper initialized successfully on {:?}", backend);
+    Ok(format!("Whisper initialized successfully ({:?})", backend))
+}
+
+#[tauri::command]
+fn get_whisper_backend(state: State<'_, AppState>) -> whisper::WhisperBackendInfo {
+    whisper::WhisperBackendInfo {
+        compiled: whisper::WhisperBackend::compiled(),
+        active: state.whisper.lock().unwrap().as_ref().map(|w| w.backend()),
+    }
 }
 
 #[tauri::command]
@@ -245,29 +366,61 @@ async fn start_recording(state: State<'_, AppState>, app: AppHandle) -> Result<S
     if *is_recording {
         return Err("Already recording".to_string());
     }
+    // Locked while the listener is being switched, which counts as listening
+    if !state.hands_free.try_lock().is_ok_and(|listener| listener.is_none()) {
+        return Err("Hands-free mode is listening".to_string());
+    }
 
+    let settings = config::load_settings();
     let mut audio_capture_guard = state.audio_capture.lock().unwrap();
 
     if audio_capture_guard.is_none() {
-        let capture = AudioCapture::new()
+        let capture = AudioCapture::new(settings.audio.input_device.clone())
             .map_err(|e| format!("Failed to create audio capture: {}", e))?;
         *audio_capture_guard = Some(capture);
     }
 
-    let audio_capture = audio_capture_guard.as_ref().unwrap();
-    let stream = audio_capture
-        .start_recording()
+    let audio_capture = audio_capture_guard.as_mut().unwrap();
+    audio_capture.set_preferred_device(settings.audio.input_device.clone());
+    audio_capture.set_warm_mode(settings.audio.always_warm, settings.audio.preroll_ms);
+    audio_capture.set_max_recording_seconds(settings.whisper.max_recording_seconds);
+    audio_capture.set_channel_mix(settings.audio.channel_mix.clone());
+    audio_capture
+        .start_recording(device_lost_callback(&app))
         .map_err(|e| format!("Failed to start recording: {}", e))?;
-
-    // Store the stream
-    *state.active_stream.lock().unwrap() = Some(stream);
+    let missing_device = audio_capture.missing_device().map(|s| s.to_string());
+    let level_meter = audio_capture.level_meter();
 
     *is_recording = true;
     drop(is_recording);
     drop(audio_capture_guard);
 
+    // Notify frontend that the saved device has disappeared
+    if let Some(device_name) = missing_device {
+        let _ = app.emit("input-device-missing", device_name);
+    }
+
+    // Emit throttled input levels and clipping/silence warnings while recording
+    let app_clone = app.clone();
+    let handle = tauri::async_runtime::spawn(async move {
+        let mut monitor = LevelMonitor::new();
+        let mut interval = tokio::time::interval(LEVEL_EVENT_INTERVAL);
+        loop {
+            interval.tick().await;
+            if let Some(level) = level_meter.take() {
+                if let Some(warning) = monitor.update(&level, LEVEL_EVENT_INTERVAL) {
+                    tracing::warn!("Input level warning: {:?}", warning);
+                    let _ = app_clone.emit("audio-level-warning", warning);
+                }
+                let _ = app_clone.emit("audio-level", level);
+            }
+        }
+    });
+    if let Some(previous) = state.level_monitor_handle.lock().unwrap().replace(handle) {
+        previous.abort();
+    }
+
     // Get max recording time from settings
-    let settings = config::load_settings();
     let max_seconds = settings.whisper.max_recording_seconds;
 
     // Start auto-stop timer
@@ -276,19 +429,38 @@ async fn start_recording(state: State<'_, AppState>, app: AppHandle) -> Result<S
         let handle = tauri::async_runtime::spawn(async move {
             tokio::time::sleep(std::time::Duration::from_secs(max_seconds as u64)).await;
             tracing::info!("Auto-stop timer triggered after {} seconds", max_seconds);
-            let state: State<AppState> = app_clone.state();
-            // Check if still recording before auto-stopping
-            if *state.is_recording.lock().unwrap() {
-                let _ = app_clone.emit("recording-auto-stopped", ());
-                let app_for_stop = app_clone.clone();
-                if let Err(e) = stop_recording(state, app_for_stop).await {
-                    tracing::error!("Failed to auto-stop recording: {}", e);
-                }
-            }
+            auto_stop_recording(app_clone, AutoStopReason::MaxLength).await;
         });
         *state.auto_stop_handle.lock().unwrap() = Some(handle);
     }
 
+    // Stop once the speaker has been silent for a while
+    let silence_ms = settings.whisper.auto_stop_silence_ms;
+    if silence_ms > 0 {
+        let stop = Arc::new(AtomicBool::new(false));
+        if let Some(previous) = state.silence_monitor.lock().unwrap().replace(stop.clone()) {
+            previous.store(true, Ordering::Release);
+        }
+        let app_clone = app.clone();
+        std::thread::spawn(move || run_silence_monitor(app_clone, stop, silence_ms));
+    }
+
+    // Show partial results while recording
+    if settings.whisper.live.enabled {
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_clone = stop.clone();
+        let app_clone = app.clone();
+        let thread = std::thread::spawn(move || run_live_transcription(app_clone, stop_clone));
+        if let Some(previous) = state
+            .live_transcription
+            .lock()
+            .unwrap()
+            .replace(LiveTranscription { stop, thread })
+        {
+            previous.stop.store(true, Ordering::Release);
+        }
+    }
+
     // Notify frontend
     app.emit("recording-started", ())
         .map_err(|e| format!("Failed to emit event: {}", e))?;
@@ -306,21 +478,29 @@ async fn stop_recording(state: State<'_, AppState>, app: AppHandle) -> Result<St
             tracing::info!("Auto-stop timer cancelled");
         }
     }
+    if let Some(stop) = state.silence_monitor.lock().unwrap().take() {
+        stop.store(true, Ordering::Release);
+    }
+    let live_transcription = state.live_transcription.lock().unwrap().take();
+    if let Some(live) = &live_transcription {
+        live.stop.store(true, Ordering::Release);
+    }
 
-    // Phase 1: Gather all data while holding locks, then release them before any await
-    let text = {
+    // Stop level metering
+    if let Some(handle) = state.level_monitor_handle.lock().unwrap().take() {
+        handle.abort();
+    }
+
+    let (audio_data, sample_rate) = {
         let mut is_recording = state.is_recording.lock().unwrap();
 
         if !*is_recording {
             return Err("Not recording".to_string());
         }
 
-        // Drop the stream to stop recording
-        *state.active_stream.lock().unwrap() = None;
-
-        let audio_capture_guard = state.audio_capture.lock().unwrap();
+        let mut audio_capture_guard = state.audio_capture.lock().unwrap();
         let audio_capture = audio_capture_guard
-            .as_ref()
+            .as_mut()
             .ok_or("Audio capture not initialized")?;
 
         let audio_data = audio_capture.stop_recording();
@@ -330,78 +510,476 @@ async fn stop_recording(state: State<'_, AppState>, app: AppHandle) -> Result<St
         drop(is_recording);
         drop(audio_capture_guard);
 
-        // Notify frontend
-        app.emit("recording-stopped", ())
-            .map_err(|e| format!("Failed to emit event: {}", e))?;
+        (audio_data, sample_rate)
+    };
 
-        tracing::info!(
-            "Recording stopped. Captured {} samples at {} Hz",
-            audio_data.len(),
-            sample_rate
+    // Pick up a device change made while recording (no-op unless warm)
+    if let Err(e) = apply_warm_capture(&app) {
+        tracing::warn!("Failed to restart warm capture: {}", e);
+    }
+
+    // Notify frontend
+    app.emit("recording-stopped", ())
+        .map_err(|e| format!("Failed to emit event: {}", e))?;
+
+    tracing::info!(
+        "Recording stopped. Captured {} samples at {} Hz",
+        audio_data.len(),
+        sample_rate
+    );
+
+    // Check if we have any audio data
+    if audio_data.is_empty() {
+        return Err("No audio data captured".to_string());
+    }
+
+    // Wait for a running pass, its state is reconciled with the end of the recording
+    let live = match live_transcription {
+        Some(live) => tauri::async_runtime::spawn_blocking(move || live.thread.join().ok())
+            .await
+            .ok()
+            .flatten(),
+        None => None,
+    };
+
+    process_audio(&state, &app, audio_data, sample_rate, AudioSource::Recording, live).await
+}
+
+/// Why a recording was stopped automatically, emitted with `recording-auto-stopped`
+#[derive(Debug, Clone, Copy, serde::Serialize)]
+enum AutoStopReason {
+    /// `max_recording_seconds` was reached
+    MaxLength,
+    /// The speaker has been silent for `auto_stop_silence_ms`
+    Silence,
+}
+
+/// Stop the recording on behalf of the max-length timer or the silence monitor
+async fn auto_stop_recording(app: AppHandle, reason: AutoStopReason) {
+    let state: State<AppState> = app.state();
+    // Check if still recording before auto-stopping
+    if *state.is_recording.lock().unwrap() {
+        let _ = app.emit("recording-auto-stopped", reason);
+        if let Err(e) = stop_recording(state, app.clone()).await {
+            tracing::error!("Failed to auto-stop recording: {}", e);
+        }
+    }
+}
+
+/// Silence monitor loop: follows the recording buffer with the streaming VAD and
+/// stops the recording after `silence_ms` of silence following speech
+fn run_silence_monitor(app: AppHandle, stop: Arc<AtomicBool>, silence_ms: u32) {
+    let state: State<AppState> = app.state();
+    let settings = config::load_settings();
+    // Samples of the recording already passed to the VAD
+    let mut offset = 0;
+    // Rebuilt when the capture sample rate changes (device reconnect)
+    let mut monitor: Option<(u32, SilenceMonitor)> = None;
+
+    while !stop.load(Ordering::Acquire) {
+        std::thread::sleep(HANDS_FREE_POLL_INTERVAL);
+
+        let Some((samples, sample_rate)) = state
+            .audio_capture
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|capture| (capture.recorded_since(offset), capture.get_sample_rate()))
+        else {
+            continue;
+        };
+        offset += samples.len();
+
+        if monitor.as_ref().is_none_or(|(rate, _)| *rate != sample_rate) {
+            let trailing = TrailingSilence::new(&settings.vad, silence_ms, AUTO_STOP_MIN_SPEECH_MS);
+            match SilenceMonitor::new(sample_rate, settings.audio.resample_quality, &settings.vad, trailing) {
+                Ok(created) => {
+                    if created.fell_back() {
+                        let _ = app.emit("vad-fallback", config::VadEngine::Energy);
+                    }
+                    monitor = Some((sample_rate, created));
+                }
+                Err(e) => {
+                    tracing::error!("Failed to create silence monitor: {}", e);
+                    return;
+                }
+            }
+        }
+
+        let (_, silence) = monitor.as_mut().unwrap();
+        match silence.process(&samples) {
+            Ok(true) => {
+                tracing::info!("Auto-stopping after {}ms of silence", silence_ms);
+                // Ignore a stop that races with the user stopping the recording
+                if !stop.load(Ordering::Acquire) {
+                    tauri::async_runtime::spawn(auto_stop_recording(app.clone(), AutoStopReason::Silence));
+                }
+                return;
+            }
+            Ok(false) => {}
+            Err(e) => tracing::warn!("Silence monitor failed: {}", e),
+        }
+    }
+}
+
+/// Live transcription loop: transcribes a sliding window of the recording every
+/// `interval_ms` and emits `transcription-partial`. Returns the transcript state,
+/// which `process_audio` completes with the rest of the recording.
+fn run_live_transcription(app: AppHandle, stop: Arc<AtomicBool>) -> LiveTranscript {
+    let state: State<AppState> = app.state();
+    let settings = config::load_settings();
+    let interval = std::time::Duration::from_millis(settings.whisper.live.interval_ms as u64);
+    let prompt = settings.whisper.prompt_for(&settings.llm.preset);
+    let mut live = LiveTranscript::new(settings.whisper.live.window_secs, settings.whisper.filter.clone());
+    // Samples of the recording already resampled
+    let mut offset = 0;
+    // Rebuilt when the capture sample rate changes (device reconnect)
+    let mut resampler: Option<(u32, StreamingResampler)> = None;
+    // Recording length at the last pass (16kHz samples)
+    let mut transcribed = 0;
+    let mut last_pass = std::time::Instant::now();
+
+    while !stop.load(Ordering::Acquire) {
+        std::thread::sleep(HANDS_FREE_POLL_INTERVAL);
+
+        let Some((samples, sample_rate)) = state
+            .audio_capture
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|capture| (capture.recorded_since(offset), capture.get_sample_rate()))
+        else {
+            continue;
+        };
+        offset += samples.len();
+
+        if resampler.as_ref().is_none_or(|(rate, _)| *rate != sample_rate) {
+            match StreamingResampler::new(sample_rate, 16000, settings.audio.resample_quality) {
+                Ok(created) => resampler = Some((sample_rate, created)),
+                Err(e) => {
+                    tracing::error!("Failed to create live transcription resampler: {}", e);
+                    return live;
+                }
+            }
+        }
+        match resampler.as_mut().unwrap().1.process(&samples) {
+            Ok(resampled) => live.push_audio(&resampled),
+            Err(e) => tracing::warn!("Live transcription resampling failed: {}", e),
+        }
+
+        if last_pass.elapsed() < interval
+            || live.window().len() < LIVE_MIN_WINDOW_SAMPLES
+            || live.recorded_samples() == transcribed
+        {
+            continue;
+        }
+        // The worker may be transcribing an earlier recording, retry on the next poll
+        let Ok(whisper_guard) = state.whisper.try_lock() else {
+            continue;
+        };
+        last_pass = std::time::Instant::now();
+        transcribed = live.recorded_samples();
+
+        let Some(whisper) = whisper_guard.as_ref() else {
+            continue;
+        };
+        match whisper.transcribe_with_options(live.window(), &settings.whisper, prompt, None) {
+            Ok(hypothesis) => {
+                let partial = live.update(&hypothesis);
+                let _ = app.emit("transcription-partial", partial);
+            }
+            Err(e) => tracing::warn!("Live transcription failed: {}", e),
+        }
+    }
+    live
+}
+
+/// Where the audio passed to `process_audio` came from
+enum AudioSource {
+    Recording,
+    /// An audio file (file name)
+    File(String),
+}
+
+/// Progress of `transcribe_file`, emitted as `file-transcription-progress`
+#[derive(Clone, serde::Serialize)]
+struct FileTranscriptionProgress {
+    file_name: String,
+    /// decoding, resampling, vad, transcribing, refining or complete
+    stage: &'static str,
+    /// Fraction of the current stage (0.0-1.0)
+    progress: f32,
+}
+
+fn emit_file_progress(app: &AppHandle, source: &AudioSource, stage: &'static str, progress: f32) {
+    if let AudioSource::File(file_name) = source {
+        let _ = app.emit(
+            "file-transcription-progress",
+            FileTranscriptionProgress {
+                file_name: file_name.clone(),
+                stage,
+                progress,
+            },
         );
+    }
+}
+
+fn emit_job_status(app: &AppHandle, id: u64, status: JobStatus, pending: usize) {
+    tracing::info!("Transcription job {}: {:?} ({} pending)", id, status, pending);
+    let _ = app.emit("transcription-job", TranscriptionJobEvent { id, status, pending });
+}
 
-        // Check if we have any audio data
-        if audio_data.is_empty() {
-            return Err("No audio data captured".to_string());
+impl TranscriptionQueue {
+    fn start(app: AppHandle) -> Self {
+        let (sender, receiver) = std::sync::mpsc::sync_channel(MAX_QUEUED_JOBS);
+        std::thread::spawn(move || run_transcription_worker(app, receiver));
+        Self {
+            sender,
+            next_id: 1,
+            jobs: Vec::new(),
         }
+    }
+}
+
+/// Runs the jobs of the transcription queue. Whisper inference blocks for
+/// seconds, so it runs here instead of on an async runtime worker.
+fn run_transcription_worker(app: AppHandle, receiver: std::sync::mpsc::Receiver<TranscriptionJob>) {
+    tracing::info!("Transcription worker started");
+    for job in receiver {
+        let state: State<AppState> = app.state();
+        let result = if job.cancel.is_cancelled() {
+            // Cancelled while it was queued
+            Err(TRANSCRIPTION_CANCELLED.to_string())
+        } else {
+            let pending = state.transcription_queue.lock().unwrap().as_ref().map_or(0, |q| q.jobs.len());
+            emit_job_status(&app, job.id, JobStatus::Running, pending);
+            tauri::async_runtime::block_on(run_pipeline(
+                &state,
+                &app,
+                job.audio_data,
+                job.sample_rate,
+                job.source,
+                job.live,
+                &job.cancel,
+            ))
+        };
+
+        let pending = {
+            let mut queue = state.transcription_queue.lock().unwrap();
+            let jobs = &mut queue.as_mut().expect("queue owns the worker").jobs;
+            jobs.retain(|(id, _)| *id != job.id);
+            jobs.len()
+        };
+        let result = match result {
+            Err(_) if job.cancel.is_cancelled() => {
+                emit_job_status(&app, job.id, JobStatus::Cancelled, pending);
+                let _ = app.emit("transcription-cancelled", job.id);
+                Err(TRANSCRIPTION_CANCELLED.to_string())
+            }
+            Err(e) => {
+                emit_job_status(&app, job.id, JobStatus::Failed, pending);
+                Err(e)
+            }
+            Ok(text) => {
+                emit_job_status(&app, job.id, JobStatus::Completed, pending);
+                Ok(text)
+            }
+        };
+        // The caller may have gone away, the job is done either way
+        let _ = job.reply.send(result);
+    }
+}
+
+/// Queue audio for the transcription worker and wait for the output text.
+///
+/// With `live`, only the audio after the live transcription window start is
+/// transcribed and reconciled with the text confirmed while recording.
+/// Until it returns, the job can be stopped with `cancel_transcription`.
+async fn process_audio(
+    state: &AppState,
+    app: &AppHandle,
+    audio_data: Vec<f32>,
+    sample_rate: u32,
+    source: AudioSource,
+    live: Option<LiveTranscript>,
+) -> Result<String, String> {
+    let (reply, result) = tokio::sync::oneshot::channel();
+    {
+        let mut queue_guard = state.transcription_queue.lock().unwrap();
+        let queue = queue_guard.get_or_insert_with(|| TranscriptionQueue::start(app.clone()));
+        let id = queue.next_id;
+        let cancel = CancelToken::default();
+        let job = TranscriptionJob {
+            id,
+            audio_data,
+            sample_rate,
+            source,
+            live,
+            cancel: cancel.clone(),
+            reply,
+        };
+        queue.sender.try_send(job).map_err(|e| match e {
+            std::sync::mpsc::TrySendError::Full(_) => "Too many transcriptions are waiting".to_string(),
+            std::sync::mpsc::TrySendError::Disconnected(_) => "Transcription worker stopped".to_string(),
+        })?;
+        queue.next_id += 1;
+        queue.jobs.push((id, cancel));
+        // Emitted under the lock, so it always comes before the worker's events
+        emit_job_status(app, id, JobStatus::Queued, queue.jobs.len());
+    }
+
+    result
+        .await
+        .map_err(|_| "Transcription worker stopped".to_string())?
+}
 
+/// Transcription pipeline shared by recordings and audio files:
+/// resample → VAD → Whisper → LLM → log → output
+///
+/// Runs on the transcription worker thread (see `process_audio`).
+async fn run_pipeline(
+    state: &AppState,
+    app: &AppHandle,
+    audio_data: Vec<f32>,
+    sample_rate: u32,
+    source: AudioSource,
+    live: Option<LiveTranscript>,
+    cancel: &CancelToken,
+) -> Result<String, String> {
+    // Phase 1: Gather all data while holding locks, then release them before any await
+    let (transcription, filtered, archive, audio_duration_secs) = {
         // Resample to 16kHz for Whisper
-        let resampler = Resampler::new(16000);
+        emit_file_progress(app, &source, "resampling", 0.0);
+        let audio_settings = config::load_settings().audio;
+        let resampler = Resampler::new(16000, audio_settings.resample_quality);
         let resampled_data = resampler
             .resample(&audio_data, sample_rate)
             .map_err(|e| format!("Failed to resample audio: {}", e))?;
 
         tracing::info!("Resampled to {} samples", resampled_data.len());
 
+        let audio_duration_secs = audio_data.len() as f32 / sample_rate as f32;
+        // Audio files are already on disk, so only recordings are archived.
+        // Written with the log entry, so a failed run leaves no orphaned file.
+        let archive = if audio_settings.archive_recordings && matches!(source, AudioSource::Recording) {
+            match audio_settings.archive_source {
+                config::ArchiveSource::Captured => Some((audio_data, sample_rate)),
+                config::ArchiveSource::Resampled => Some((resampled_data.clone(), 16000)),
+            }
+        } else {
+            None
+        };
+
+        // The live transcription has confirmed everything before its window
+        let resampled_data = match &live {
+            Some(live) => {
+                let start = live.window_start().min(resampled_data.len());
+                tracing::info!("Live transcription: transcribing from sample {}", start);
+                resampled_data[start..].to_vec()
+            }
+            None => resampled_data,
+        };
+
         // Apply VAD to extract speech segments
-        let speech_data = {
+        emit_file_progress(app, &source, "vad", 0.0);
+        let vad_settings = config::load_settings().vad;
+        // `joined` maps positions in the VAD output back to the recording (or the live window)
+        let (speech_data, joined) = if !vad_settings.enabled {
+            tracing::info!("VAD disabled, using original audio");
+            (resampled_data, None)
+        } else {
             let mut vad_guard = state.vad.lock().unwrap();
-            if vad_guard.is_none() {
-                match VadProcessor::new() {
-                    Ok(vad) => *vad_guard = Some(vad),
-                    Err(e) => tracing::warn!("Failed to create VAD, skipping: {}", e),
-                }
+            // Also after an engine change in the settings, which reloads the model
+            let fell_back = match vad_guard.as_mut() {
+                Some(vad) => vad.set_settings(&vad_settings),
+                None => vad_guard.insert(VadProcessor::new(&vad_settings)).fell_back(),
+            };
+            let vad = vad_guard.as_mut().unwrap();
+            if fell_back {
+                let _ = app.emit("vad-fallback", vad.engine());
             }
 
-            if let Some(vad) = vad_guard.as_mut() {
-                let extracted = vad.extract_speech(&resampled_data);
-                if extracted.is_empty() {
-                    tracing::info!("VAD detected no speech, using original audio");
-                    resampled_data
-                } else {
-                    extracted
-                }
+            let segments = vad.detect_segments(&resampled_data);
+            if segments.is_empty() && live.is_some() {
+                // Whisper would only hallucinate on a silent tail after the live transcription
+                tracing::info!("VAD detected no speech after the live transcription");
+                (Vec::new(), None)
+            } else if segments.is_empty() {
+                tracing::info!("VAD detected no speech, using original audio");
+                (resampled_data, None)
             } else {
-                resampled_data
+                // Keep the pauses between segments so separate sentences are not run together
+                let gap_samples = (vad_settings.segment_gap_ms as usize) * 16000 / 1000;
+                let mut joined = audio::vad::join_segments(&segments, gap_samples);
+                (std::mem::take(&mut joined.samples), Some(joined))
             }
         };
 
         tracing::info!("After VAD: {} samples", speech_data.len());
+        if cancel.is_cancelled() {
+            return Err(TRANSCRIPTION_CANCELLED.to_string());
+        }
 
         // Transcribe
+        emit_file_progress(app, &source, "transcribing", 0.0);
         app.emit("transcription-started", ())
             .map_err(|e| format!("Failed to emit event: {}", e))?;
 
-        let whisper_settings = config::load_settings().whisper;
+        let settings = config::load_settings();
+        let whisper_settings = &settings.whisper;
         let whisper_guard = state.whisper.lock().unwrap();
-        let transcribed = if let Some(whisper) = whisper_guard.as_ref() {
-            // Use Whisper for transcription
+        let mut transcription = if live.is_some() && speech_data.is_empty() {
+            // Nothing to add to the live transcription
+            whisper::TranscriptionResult::from_text(String::new())
+        } else if let Some(whisper) = whisper_guard.as_ref() {
+            // Use Whisper for transcription, with the prompt of the selected preset
+            let prompt = whisper_settings.prompt_for(&settings.llm.preset);
+            // Pauses between VAD segments, where long audio is split into chunks
+            let boundaries = joined.as_ref().map(|j| j.gap_boundaries()).unwrap_or_default();
+            // `cancel_transcription` sets the flag, which aborts Whisper mid-decode
+            let abort = Some(&cancel.flag);
             whisper
-                .transcribe_with_options(&speech_data, whisper_settings.insert_newline)
+                .transcribe_long(&speech_data, whisper_settings, prompt, &boundaries, abort, |progress| {
+                    emit_file_progress(app, &source, "transcribing", progress)
+                })
                 .map_err(|e| format!("Failed to transcribe: {}", e))?
         } else {
             // Fallback to dummy mode if Whisper not initialized
             tracing::warn!("Whisper not initialized, using dummy mode");
-            format!(
+            whisper::TranscriptionResult::from_text(format!(
                 "[デモモード] {}サンプルの音声を録音しました。モデルを読み込んでください。",
                 speech_data.len()
-            )
+            ))
         };
         drop(whisper_guard);
 
-        tracing::info!("Transcription result: {}", transcribed);
-        transcribed
+        // Segment times are relative to the VAD output, report them in recording time
+        // (window time with live transcription, which `LiveTranscript::finish` converts)
+        if let Some(joined) = &joined {
+            transcription.map_times(|secs| {
+                joined.to_original((secs * 16000.0) as usize) as f32 / 16000.0
+            });
+        }
+        // Drop hallucinated phrases and runaway repetition before anything is output.
+        // The live transcription filters every window before reconciling its text.
+        let filtered = if let Some(live) = live {
+            let (finished, removals) = live.finish(transcription);
+            transcription = finished;
+            removals
+        } else {
+            let insert_newline = whisper_settings.insert_newline;
+            whisper::filter_transcription(&mut transcription, &whisper_settings.filter, insert_newline)
+        };
+        let _ = app.emit("transcription-result", &transcription);
+
+        tracing::info!(
+            "Transcription result ({}): {}",
+            transcription.language.as_deref().unwrap_or("unknown"),
+            transcription.text
+        );
+        (transcription, filtered, archive, audio_duration_secs)
     };
+    let text = transcription.text.clone();
     // All MutexGuards are now dropped
 
     // Phase 2: LLM refinement (async, no locks held)
@@ -411,6 +989,7 @@ async fn stop_recording(state: State<'_, AppState>, app: AppHandle) -> Result<St
             "LLM refinement enabled, sending to {:?}...",
             settings.llm.provider
         );
+        emit_file_progress(app, &source, "refining", 0.0);
         app.emit("llm-refinement-started", ())
             .map_err(|e| format!("Failed to emit event: {}", e))?;
 
@@ -421,7 +1000,12 @@ async fn stop_recording(state: State<'_, AppState>, app: AppHandle) -> Result<St
         );
         let prompt_template = settings.llm.get_prompt_template();
         tracing::info!("Using prompt preset: {:?}", settings.llm.preset);
-        match llm_client.refine_text_with_prompt(&text, &prompt_template).await {
+        // Dropping the request future aborts the HTTP request
+        let refined = tokio::select! {
+            refined = llm_client.refine_text_with_prompt(&text, &prompt_template) => refined,
+            _ = cancel.cancelled() => return Err(TRANSCRIPTION_CANCELLED.to_string()),
+        };
+        match refined {
             Ok(refined) => {
                 tracing::info!("LLM refined: {} -> {}", text, refined);
                 app.emit("llm-refinement-complete", refined.clone())
@@ -439,30 +1023,36 @@ async fn stop_recording(state: State<'_, AppState>, app: AppHandle) -> Result<St
         text.clone()
     };
 
-    // Phase 3: Save log entry
-    {
-        if let Ok(log_manager) = log::LogManager::new() {
-            let refined = if settings.llm.enabled {
-                Some(final_text.clone())
-            } else {
-                None
-            };
-            let preset_name = if settings.llm.enabled {
-                Some(format!("{:?}", settings.llm.preset))
-            } else {
-                None
-            };
-            if let Err(e) = log_manager.add_entry(
-                text.clone(),
-                refined,
-                None, // audio_duration_secs - could be calculated if needed
-                settings.llm.enabled,
-                preset_name,
-            ) {
-                tracing::warn!("Failed to save log entry: {}", e);
-            }
-        }
-    }
+    // Phase 3: Save log entry, with the archived recording
+    let source_file = match &source {
+        AudioSource::Recording => None,
+        AudioSource::File(file_name) => Some(file_name.clone()),
+    };
+    let refined = if settings.llm.enabled {
+        Some(final_text.clone())
+    } else {
+        None
+    };
+    let preset_name = if settings.llm.enabled {
+        Some(format!("{:?}", settings.llm.preset))
+    } else {
+        None
+    };
+    let entry = log::NewLogEntry {
+        raw_text: text.clone(),
+        refined_text: refined,
+        audio_duration_secs: Some(audio_duration_secs),
+        llm_used: settings.llm.enabled,
+        prompt_preset: preset_name,
+        audio_path: None,
+        source_file,
+        language: transcription.language,
+        translated: settings.whisper.translate,
+        segments: transcription.segments,
+        filtered,
+    };
+    let audio = archive.as_ref().map(|(samples, sample_rate)| (samples.as_slice(), *sample_rate));
+    save_log_entry(log::LogManager::new().ok().as_ref(), cancel, entry, audio)?;
 
     // Phase 4: Copy to clipboard (sync, brief lock)
     {
@@ -473,19 +1063,321 @@ async fn stop_recording(state: State<'_, AppState>, app: AppHandle) -> Result<St
             *clipboard_guard = Some(clipboard);
         }
 
+        // The app window has focus while transcribing a file, so never paste into it
+        let output_mode = match &source {
+            AudioSource::Recording => settings.output_mode.clone(),
+            AudioSource::File(_) => config::OutputMode::ClipboardOnly,
+        };
         let clipboard = clipboard_guard.as_mut().unwrap();
         clipboard
-            .output_text(&final_text, &settings.output_mode)
+            .output_text(&final_text, &output_mode)
             .map_err(|e| format!("Failed to output text: {}", e))?;
     }
 
     // Notify frontend with result
+    emit_file_progress(app, &source, "complete", 1.0);
     app.emit("transcription-complete", final_text.clone())
         .map_err(|e| format!("Failed to emit event: {}", e))?;
 
     Ok(final_text)
 }
 
+/// Saves the log entry of a run and its archived recording, unless the run was cancelled.
+/// This is the last point a run can be cancelled: nothing has been written or output before it,
+/// so a cancelled run leaves no archive, log entry or clipboard text behind
+fn save_log_entry(
+    log_manager: Option<&log::LogManager>,
+    cancel: &CancelToken,
+    entry: log::NewLogEntry,
+    audio: Option<(&[f32], u32)>,
+) -> Result<(), String> {
+    if cancel.is_cancelled() {
+        return Err(TRANSCRIPTION_CANCELLED.to_string());
+    }
+    if let Some(log_manager) = log_manager {
+        if let Err(e) = log_manager.add_entry_with_audio(entry, audio) {
+            tracing::warn!("Failed to save log entry: {}", e);
+        }
+    }
+    Ok(())
+}
+
+#[tauri::command]
+async fn transcribe_file(state: State<'_, AppState>, app: AppHandle, path: String) -> Result<String, String> {
+    let path = PathBuf::from(path);
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.to_string_lossy().into_owned());
+    let source = AudioSource::File(file_name.clone());
+    tracing::info!("Transcribing file: {}", path.display());
+
+    // Decoding is CPU-bound, keep it off the async runtime
+    emit_file_progress(&app, &source, "decoding", 0.0);
+    let app_clone = app.clone();
+    let decoded = tauri::async_runtime::spawn_blocking(move || {
+        let source = AudioSource::File(file_name);
+        audio::decode::decode_file(&path, |progress| {
+            emit_file_progress(&app_clone, &source, "decoding", progress);
+        })
+        .map_err(|e| format!("Failed to decode audio file: {}", e))
+    })
+    .await
+    .map_err(|e| format!("Decode task failed: {}", e))??;
+
+    process_audio(&state, &app, decoded.samples, decoded.sample_rate, source, None).await
+}
+
+#[tauri::command]
+fn get_supported_audio_extensions() -> Vec<&'static str> {
+    audio::decode::SUPPORTED_EXTENSIONS.to_vec()
+}
+
+/// Build the callback the capture layer invokes when the recording device disappears
+fn device_lost_callback(app: &AppHandle) -> DeviceLostCallback {
+    let app = app.clone();
+    Arc::new(move || {
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            handle_device_lost(app).await;
+        });
+    })
+}
+
+/// Handle a recording device that was unplugged mid-recording: either resume on the
+/// next available device or stop and transcribe what was captured so far
+async fn handle_device_lost(app: AppHandle) {
+    let state: State<AppState> = app.state();
+    if !*state.is_recording.lock().unwrap() {
+        // The warm (idle) or hands-free stream was lost; reopen it on whatever device is available now
+        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+        if let Err(e) = apply_warm_capture(&app) {
+            tracing::warn!("Failed to restart warm capture: {}", e);
+        }
+        if let Err(e) = apply_hands_free(&app).await {
+            tracing::warn!("Failed to restart hands-free mode: {}", e);
+        }
+        return;
+    }
+
+    let device_name = state
+        .audio_capture
+        .lock()
+        .unwrap()
+        .as_ref()
+        .and_then(|c| c.device_name().map(|s| s.to_string()))
+        .unwrap_or_default();
+    tracing::warn!("Recording device lost: {}", device_name);
+    let _ = app.emit("recording-device-lost", device_name);
+
+    if config::load_settings().audio.reconnect_on_device_lost {
+        // Give the OS a moment to settle on a new default device
+        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+
+        // The recording may have been stopped while waiting
+        if !*state.is_recording.lock().unwrap() {
+            return;
+        }
+        let result = state
+            .audio_capture
+            .lock()
+            .unwrap()
+            .as_mut()
+            .map(|capture| capture.reconnect());
+
+        match result {
+            Some(Ok(name)) => {
+                let _ = app.emit("recording-device-reconnected", name);
+                return;
+            }
+            Some(Err(e)) => tracing::warn!("Failed to reconnect recording device: {}", e),
+            None => return,
+        }
+    }
+
+    // Keep what was captured so far and process it as a normal recording
+    if let Err(e) = stop_recording(state, app.clone()).await {
+        tracing::error!("Failed to stop recording after device loss: {}", e);
+    }
+}
+
+/// Apply the always-warm capture settings: open the idle stream so the pre-roll
+/// starts filling, or close it when warm mode is disabled
+fn apply_warm_capture(app: &AppHandle) -> Result<(), String> {
+    let state: State<AppState> = app.state();
+    let settings = config::load_settings();
+    let is_recording = *state.is_recording.lock().unwrap();
+
+    let mut audio_capture_guard = state.audio_capture.lock().unwrap();
+    if audio_capture_guard.is_none() {
+        if !settings.audio.always_warm {
+            return Ok(());
+        }
+        let capture = AudioCapture::new(settings.audio.input_device.clone())
+            .map_err(|e| format!("Failed to create audio capture: {}", e))?;
+        *audio_capture_guard = Some(capture);
+    }
+
+    let audio_capture = audio_capture_guard.as_mut().unwrap();
+    audio_capture.set_warm_mode(settings.audio.always_warm, settings.audio.preroll_ms);
+    // A device change mid-recording is picked up when the recording stops
+    if is_recording {
+        return Ok(());
+    }
+    audio_capture.set_preferred_device(settings.audio.input_device.clone());
+    audio_capture.set_channel_mix(settings.audio.channel_mix.clone());
+    audio_capture
+        .start_warm(device_lost_callback(app))
+        .map_err(|e| format!("Failed to start warm capture: {}", e))
+}
+
+/// Start or stop the hands-free listener according to the settings. A running
+/// listener is restarted so it picks up changed device and VAD settings.
+async fn apply_hands_free(app: &AppHandle) -> Result<(), String> {
+    let state: State<AppState> = app.state();
+    let mut listener = state.hands_free.lock().await;
+    stop_hands_free(app, listener.take()).await;
+
+    let settings = config::load_settings();
+    if !settings.hands_free.enabled {
+        return Ok(());
+    }
+    if *state.is_recording.lock().unwrap() {
+        return Err("Cannot start hands-free mode while recording".to_string());
+    }
+
+    {
+        let mut audio_capture_guard = state.audio_capture.lock().unwrap();
+        if audio_capture_guard.is_none() {
+            let capture = AudioCapture::new(settings.audio.input_device.clone())
+                .map_err(|e| format!("Failed to create audio capture: {}", e))?;
+            *audio_capture_guard = Some(capture);
+        }
+
+        let audio_capture = audio_capture_guard.as_mut().unwrap();
+        audio_capture.set_preferred_device(settings.audio.input_device.clone());
+        audio_capture.set_warm_mode(settings.audio.always_warm, settings.audio.preroll_ms);
+        audio_capture.set_max_recording_seconds(settings.whisper.max_recording_seconds);
+        audio_capture.set_channel_mix(settings.audio.channel_mix.clone());
+        audio_capture
+            .start_recording(device_lost_callback(app))
+            .map_err(|e| format!("Failed to start hands-free capture: {}", e))?;
+    }
+
+    // Utterances are transcribed one at a time, in the order they were spoken
+    let (utterance_tx, mut utterance_rx) = tokio::sync::mpsc::unbounded_channel::<Vec<f32>>();
+    let app_clone = app.clone();
+    tauri::async_runtime::spawn(async move {
+        while let Some(samples) = utterance_rx.recv().await {
+            let state: State<AppState> = app_clone.state();
+            if let Err(e) = process_audio(&state, &app_clone, samples, 16000, AudioSource::Recording, None).await {
+                tracing::error!("Failed to process utterance: {}", e);
+            }
+        }
+    });
+
+    let stop = Arc::new(AtomicBool::new(false));
+    let stop_clone = stop.clone();
+    let app_clone = app.clone();
+    let thread = std::thread::spawn(move || run_hands_free(app_clone, stop_clone, utterance_tx));
+    *listener = Some(HandsFreeListener { stop, thread });
+
+    tracing::info!("Hands-free mode started");
+    let _ = app.emit("hands-free-started", ());
+    Ok(())
+}
+
+/// Stop a hands-free listener (if running) and release the capture stream.
+/// An utterance in progress is still transcribed.
+async fn stop_hands_free(app: &AppHandle, listener: Option<HandsFreeListener>) {
+    let Some(listener) = listener else {
+        return;
+    };
+
+    listener.stop.store(true, Ordering::Release);
+    // The listener finishes its current VAD pass first, so wait for it off the async runtime
+    let joined = tauri::async_runtime::spawn_blocking(move || listener.thread.join()).await;
+    if !matches!(joined, Ok(Ok(()))) {
+        tracing::error!("Hands-free listener panicked");
+    }
+    let state: State<AppState> = app.state();
+    if let Some(capture) = state.audio_capture.lock().unwrap().as_mut() {
+        capture.stop_recording();
+    }
+
+    tracing::info!("Hands-free mode stopped");
+    let _ = app.emit("hands-free-stopped", ());
+}
+
+/// Hands-free listener loop: reads the capture buffer, runs the streaming VAD and
+/// sends every finished utterance to the transcription task
+fn run_hands_free(app: AppHandle, stop: Arc<AtomicBool>, utterance_tx: tokio::sync::mpsc::UnboundedSender<Vec<f32>>) {
+    let state: State<AppState> = app.state();
+    // Rebuilt when the capture sample rate changes (device reconnect)
+    let mut streaming: Option<(u32, StreamingVad)> = None;
+
+    let handle_event = |event: UtteranceEvent| match event {
+        UtteranceEvent::Started => {
+            let _ = app.emit("utterance-started", ());
+        }
+        UtteranceEvent::Discarded => {
+            let _ = app.emit("utterance-discarded", ());
+        }
+        UtteranceEvent::Ended(samples) => {
+            tracing::info!("Utterance detected: {} samples", samples.len());
+            let _ = app.emit("utterance-ended", ());
+            let _ = utterance_tx.send(samples);
+        }
+    };
+
+    while !stop.load(Ordering::Acquire) {
+        std::thread::sleep(HANDS_FREE_POLL_INTERVAL);
+
+        let Some((samples, sample_rate)) = state
+            .audio_capture
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|capture| (capture.take_recorded(), capture.get_sample_rate()))
+        else {
+            continue;
+        };
+
+        if streaming.as_ref().is_none_or(|(rate, _)| *rate != sample_rate) {
+            let settings = config::load_settings();
+            let utterance_config = UtteranceConfig::new(
+                &settings.vad,
+                settings.hands_free.silence_timeout_ms,
+                settings.hands_free.min_speech_ms,
+                settings.whisper.max_recording_seconds,
+            );
+            match StreamingVad::new(sample_rate, settings.audio.resample_quality, &settings.vad, utterance_config) {
+                Ok(vad) => {
+                    if vad.fell_back() {
+                        let _ = app.emit("vad-fallback", config::VadEngine::Energy);
+                    }
+                    streaming = Some((sample_rate, vad));
+                }
+                Err(e) => {
+                    tracing::error!("Failed to create streaming VAD: {}", e);
+                    let _ = app.emit("hands-free-error", e.to_string());
+                    return;
+                }
+            }
+        }
+
+        let (_, vad) = streaming.as_mut().unwrap();
+        match vad.process(&samples) {
+            Ok(events) => events.into_iter().for_each(&handle_event),
+            Err(e) => tracing::warn!("Streaming VAD failed: {}", e),
+        }
+    }
+
+    if let Some(event) = streaming.as_mut().and_then(|(_, vad)| vad.finish()) {
+        handle_event(event);
+    }
+}
+
 #[tauri::command]
 async fn toggle_recording(state: State<'_, AppState>, app: AppHandle) -> Result<String, String> {
     let is_recording = *state.is_recording.lock().unwrap();
@@ -528,6 +1420,158 @@ fn save_max_recording_seconds(max_seconds: u32) -> Result<(), String> {
     config::save_settings(&settings)
 }
 
+#[tauri::command]
+fn save_auto_stop_silence(silence_ms: u32) -> Result<(), String> {
+    let mut settings = config::load_settings();
+    settings.whisper.auto_stop_silence_ms = silence_ms;
+    config::save_settings(&settings)
+}
+
+#[tauri::command]
+fn save_transcription_language(language: String, translate: bool) -> Result<(), String> {
+    let mut settings = config::load_settings();
+    settings.whisper.language = language;
+    settings.whisper.translate = translate;
+    settings.whisper.validate();
+    config::save_settings(&settings)
+}
+
+#[tauri::command]
+fn save_whisper_prompts(
+    prompt: config::WhisperPrompt,
+    preset_prompts: std::collections::HashMap<config::PromptPreset, config::WhisperPrompt>,
+) -> Result<(), String> {
+    let mut settings = config::load_settings();
+    settings.whisper.prompt = prompt;
+    settings.whisper.preset_prompts = preset_prompts;
+    settings.whisper.validate();
+    config::save_settings(&settings)
+}
+
+#[tauri::command]
+fn save_decoding_settings(decoding: config::DecodingSettings) -> Result<(), String> {
+    let mut settings = config::load_settings();
+    settings.whisper.decoding = decoding;
+    settings.whisper.validate();
+    config::save_settings(&settings)
+}
+
+#[tauri::command]
+fn save_live_transcription_settings(mut live: config::LiveTranscriptionSettings) -> Result<(), String> {
+    live.validate();
+    let mut settings = config::load_settings();
+    settings.whisper.live = live;
+    config::save_settings(&settings)
+}
+
+#[tauri::command]
+fn save_output_filter_settings(mut filter: config::OutputFilterSettings) -> Result<(), String> {
+    filter.validate();
+    let mut settings = config::load_settings();
+    settings.whisper.filter = filter;
+    config::save_settings(&settings)
+}
+
+/// GPU settings take effect the next time the model is loaded
+#[tauri::command]
+fn save_gpu_settings(use_gpu: bool, gpu_device: u32) -> Result<(), String> {
+    let mut settings = config::load_settings();
+    settings.whisper.use_gpu = use_gpu;
+    settings.whisper.gpu_device = gpu_device;
+    config::save_settings(&settings)
+}
+
+#[tauri::command]
+fn get_input_devices() -> Result<Vec<audio::InputDeviceInfo>, String> {
+    audio::list_input_devices().map_err(|e| format!("Failed to list input devices: {}", e))
+}
+
+#[tauri::command]
+async fn save_input_device(app: AppHandle, device_name: Option<String>) -> Result<(), String> {
+    let mut settings = config::load_settings();
+    let input_device = device_name.filter(|name| !name.is_empty());
+    let changed = settings.audio.input_device != input_device;
+    settings.audio.input_device = input_device;
+    config::save_settings(&settings)?;
+    if !changed {
+        return Ok(());
+    }
+    // Move a warm or hands-free stream over to the new device right away
+    apply_warm_capture(&app)?;
+    apply_hands_free(&app).await
+}
+
+#[tauri::command]
+fn save_reconnect_on_device_lost(enabled: bool) -> Result<(), String> {
+    let mut settings = config::load_settings();
+    settings.audio.reconnect_on_device_lost = enabled;
+    config::save_settings(&settings)
+}
+
+#[tauri::command]
+async fn save_channel_mix(app: AppHandle, channel_mix: config::ChannelMix) -> Result<(), String> {
+    let mut settings = config::load_settings();
+    let changed = settings.audio.channel_mix != channel_mix;
+    settings.audio.channel_mix = channel_mix;
+    config::save_settings(&settings)?;
+    if !changed {
+        return Ok(());
+    }
+    apply_warm_capture(&app)?;
+    apply_hands_free(&app).await
+}
+
+#[tauri::command]
+fn save_archive_settings(archive_recordings: bool, archive_source: config::ArchiveSource) -> Result<(), String> {
+    let mut settings = config::load_settings();
+    settings.audio.archive_recordings = archive_recordings;
+    settings.audio.archive_source = archive_source;
+    config::save_settings(&settings)
+}
+
+#[tauri::command]
+async fn save_vad_settings(app: AppHandle, mut vad: config::VadSettings) -> Result<(), String> {
+    vad.validate();
+    let mut settings = config::load_settings();
+    let changed = settings.vad != vad;
+    settings.vad = vad;
+    config::save_settings(&settings)?;
+    // Restarting the listener ends an utterance in progress, so only do it for a change
+    if changed {
+        apply_hands_free(&app).await?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+async fn save_hands_free_settings(app: AppHandle, mut hands_free: config::HandsFreeSettings) -> Result<(), String> {
+    hands_free.validate();
+    let mut settings = config::load_settings();
+    let changed = settings.hands_free != hands_free;
+    settings.hands_free = hands_free;
+    config::save_settings(&settings)?;
+    if changed {
+        apply_hands_free(&app).await?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+fn save_resample_quality(resample_quality: config::ResampleQuality) -> Result<(), String> {
+    let mut settings = config::load_settings();
+    settings.audio.resample_quality = resample_quality;
+    config::save_settings(&settings)
+}
+
+#[tauri::command]
+fn save_warm_capture_settings(app: AppHandle, always_warm: bool, preroll_ms: u32) -> Result<(), String> {
+    let mut settings = config::load_settings();
+    settings.audio.always_warm = always_warm;
+    settings.audio.preroll_ms = preroll_ms;
+    config::save_settings(&settings)?;
+    apply_warm_capture(&app)
+}
+
 #[tauri::command]
 fn save_llm_settings(
     enabled: bool,
@@ -583,6 +1627,49 @@ fn save_output_mode(mode: String) -> Result<(), String> {
     config::save_settings(&settings)
 }
 
+/// Cancel a queued or running transcription job (the running one without `job_id`)
+#[tauri::command]
+fn cancel_transcription(state: State<'_, AppState>, job_id: Option<u64>) -> Result<(), String> {
+    let queue = state.transcription_queue.lock().unwrap();
+    let jobs = queue.as_ref().map(|q| q.jobs.as_slice()).unwrap_or_default();
+    let (id, cancel) = match job_id {
+        Some(job_id) => jobs.iter().find(|(id, _)| *id == job_id),
+        None => jobs.first(),
+    }
+    .ok_or("No transcription in progress")?;
+    tracing::info!("Cancelling transcription job {}", id);
+    cancel.cancel();
+    Ok(())
+}
+
+/// Register the cancel shortcut from the settings (none when it is empty)
+fn register_cancel_shortcut(app: &AppHandle) -> Result<(), String> {
+    let shortcut = config::load_settings().shortcut.cancel;
+    if shortcut.is_empty() {
+        return Ok(());
+    }
+    ShortcutHandler::register_action(app, &shortcut, |app_handle| {
+        let state: State<AppState> = app_handle.state();
+        if let Err(e) = cancel_transcription(state, None) {
+            tracing::info!("Cancel shortcut ignored: {}", e);
+        }
+    })
+    .map_err(|e| e.to_string())
+}
+
+/// The recording and cancel shortcuts must be different keys
+fn check_shortcut_conflict(recording_toggle: &str, cancel: &str) -> Result<(), String> {
+    if cancel.is_empty() {
+        return Ok(());
+    }
+    let toggle = shortcuts::parse_shortcut(recording_toggle).map_err(|e| format!("Invalid shortcut: {}", e))?;
+    let cancel = shortcuts::parse_shortcut(cancel).map_err(|e| format!("Invalid shortcut: {}", e))?;
+    if toggle == cancel {
+        return Err("The cancel shortcut must differ from the recording shortcut".to_string());
+    }
+    Ok(())
+}
+
 #[tauri::command]
 fn get_shortcut_setting() -> String {
     let settings = config::load_settings();
@@ -595,6 +1682,8 @@ fn save_shortcut_setting(app: AppHandle, state: State<'_, AppState>, shortcut: S
     shortcuts::parse_shortcut(&shortcut)
         .map_err(|e| format!("Invalid shortcut: {}", e))?;
 
+    check_shortcut_conflict(&shortcut, &config::load_settings().shortcut.cancel)?;
+
     // Get current shortcut
     let current = state.current_shortcut.lock().unwrap().clone();
 
@@ -612,6 +1701,10 @@ fn save_shortcut_setting(app: AppHandle, state: State<'_, AppState>, shortcut: S
     // Update shortcut registration
     ShortcutHandler::update_shortcut(&app, &current, &shortcut, toggle_callback)
         .map_err(|e| e.to_string())?;
+    // update_shortcut unregisters every shortcut
+    if let Err(e) = register_cancel_shortcut(&app) {
+        tracing::warn!("Failed to register cancel shortcut: {}", e);
+    }
 
     // Update state
     *state.current_shortcut.lock().unwrap() = shortcut.clone();
@@ -622,6 +1715,22 @@ fn save_shortcut_setting(app: AppHandle, state: State<'_, AppState>, shortcut: S
     config::save_settings(&settings)
 }
 
+#[tauri::command]
+fn save_cancel_shortcut(app: AppHandle, state: State<'_, AppState>, shortcut: String) -> Result<(), String> {
+    let current = state.current_shortcut.lock().unwrap().clone();
+    check_shortcut_conflict(&current, &shortcut)?;
+
+    let mut settings = config::load_settings();
+    if !settings.shortcut.cancel.is_empty() {
+        if let Err(e) = ShortcutHandler::unregister(&app, &settings.shortcut.cancel) {
+            tracing::warn!("Failed to unregister cancel shortcut: {}", e);
+        }
+    }
+    settings.shortcut.cancel = shortcut;
+    config::save_settings(&settings)?;
+    register_cancel_shortcut(&app)
+}
+
 #[tauri::command]
 async fn check_llm_status(api_url: String, provider: String) -> Result<bool, String> {
     let llm_provider = match provider.as_str() {
@@ -798,6 +1907,10 @@ pub fn run() {
                 }
             }
 
+            if let Err(e) = register_cancel_shortcut(app.handle()) {
+                tracing::error!("Failed to register cancel shortcut: {}", e);
+            }
+
             // Setup system tray
             let tray_manager = TrayManager::new();
             if let Err(e) = tray_manager.setup(app.handle()) {
@@ -805,6 +1918,17 @@ pub fn run() {
             }
             tracing::info!("Setup complete - tray and shortcuts registered (shortcut: {})", shortcut_str);
 
+            // Open the microphone early when always-warm capture is enabled
+            if let Err(e) = apply_warm_capture(app.handle()) {
+                tracing::error!("Failed to start warm capture: {}", e);
+            }
+            let app_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = apply_hands_free(&app_handle).await {
+                    tracing::error!("Failed to start hands-free mode: {}", e);
+                }
+            });
+
             Ok(())
         })
         .on_window_event(|window, event| {
@@ -823,16 +1947,37 @@ pub fn run() {
             start_recording,
             stop_recording,
             toggle_recording,
+            cancel_transcription,
+            transcribe_file,
+            get_supported_audio_extensions,
             get_settings,
             save_model_selection,
             save_whisper_insert_newline,
             save_max_recording_seconds,
+            save_auto_stop_silence,
+            save_transcription_language,
+            save_whisper_prompts,
+            save_decoding_settings,
+            save_gpu_settings,
+            save_live_transcription_settings,
+            save_output_filter_settings,
+            get_whisper_backend,
+            get_input_devices,
+            save_input_device,
+            save_reconnect_on_device_lost,
+            save_warm_capture_settings,
+            save_channel_mix,
+            save_archive_settings,
+            save_resample_quality,
+            save_vad_settings,
+            save_hands_free_settings,
             save_llm_settings,
             save_prompt_settings,
             get_preset_prompts,
             save_output_mode,
             get_shortcut_setting,
             save_shortcut_setting,
+            save_cancel_shortcut,
             check_llm_status,
             get_recent_logs,
             get_logs_for_date,
@@ -845,3 +1990,30 @@ pub fn run() {
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancelled_run_writes_no_archive() {
+        let dir = std::env::temp_dir().join(format!("pipeline-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let log_manager = log::LogManager::in_dir(&dir);
+        let audio = Some((&[0.0, 0.5][..], 16000));
+
+        let cancel = CancelToken::default();
+        cancel.cancel();
+        let result = save_log_entry(Some(&log_manager), &cancel, log::NewLogEntry::default(), audio);
+        assert_eq!(result, Err(TRANSCRIPTION_CANCELLED.to_string()));
+        assert!(!dir.join("audio").exists());
+        assert!(log_manager.get_recent_logs(10).is_empty());
+
+        let result = save_log_entry(Some(&log_manager), &CancelToken::default(), log::NewLogEntry::default(), audio);
+        assert!(result.is_ok());
+        assert_eq!(std::fs::read_dir(dir.join("audio")).unwrap().count(), 1);
+        assert_eq!(log_manager.get_recent_logs(10).len(), 1);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -3,6 +3,10 @@ use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
 
+use crate::audio::archive::write_wav;
+
+use crate::whisper::{FilterRemoval, TranscriptSegment};
+
 /// A single transcription log entry
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LogEntry {
@@ -20,23 +24,79 @@ pub struct LogEntry {
     pub llm_used: bool,
     /// Prompt preset used (if LLM was enabled)
     pub prompt_preset: Option<String>,
+    /// Archived recording (if audio archiving was enabled)
+    #[serde(default)]
+    pub audio_path: Option<String>,
+    /// Name of the transcribed audio file (None for microphone recordings)
+    #[serde(default)]
+    pub source_file: Option<String>,
+    /// Spoken language code (configured or detected by Whisper)
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Whether Whisper translated the speech to English
+    #[serde(default)]
+    pub translated: bool,
+    /// Whisper segments with times in the recording and confidence
+    #[serde(default)]
+    pub segments: Vec<TranscriptSegment>,
+    /// Text removed by the hallucination filter
+    #[serde(default)]
+    pub filtered: Vec<FilterRemoval>,
+}
+
+/// Contents of a new log entry (the ID and timestamp are assigned by `add_entry`)
+#[derive(Debug, Clone, Default)]
+pub struct NewLogEntry {
+    pub raw_text: String,
+    pub refined_text: Option<String>,
+    pub audio_duration_secs: Option<f32>,
+    pub llm_used: bool,
+    pub prompt_preset: Option<String>,
+    pub audio_path: Option<String>,
+    pub source_file: Option<String>,
+    pub language: Option<String>,
+    pub translated: bool,
+    pub segments: Vec<TranscriptSegment>,
+    pub filtered: Vec<FilterRemoval>,
+}
+
+/// Remove the archived audio of deleted entries
+fn remove_audio_files<'a>(entries: impl IntoIterator<Item = &'a LogEntry>) {
+    for path in entries.into_iter().filter_map(|e| e.audio_path.as_ref()) {
+        match fs::remove_file(path) {
+            Ok(()) => tracing::info!("Deleted audio file: {}", path),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => tracing::warn!("Failed to delete audio file {}: {}", path, e),
+        }
+    }
 }
 
 /// Log manager for storing and retrieving transcription logs
 pub struct LogManager {
     log_dir: PathBuf,
+    audio_dir: PathBuf,
 }
 
 impl LogManager {
     /// Create a new LogManager
     pub fn new() -> Result<Self, String> {
         let appdata = std::env::var("APPDATA").unwrap_or_else(|_| ".".to_string());
-        let log_dir = PathBuf::from(appdata).join("voice-input").join("logs");
+        let app_dir = PathBuf::from(appdata).join("voice-input");
+        let log_dir = app_dir.join("logs");
+        let audio_dir = app_dir.join("audio");
 
         // Create log directory if it doesn't exist
         fs::create_dir_all(&log_dir).map_err(|e| format!("Failed to create log directory: {}", e))?;
 
-        Ok(Self { log_dir })
+        Ok(Self { log_dir, audio_dir })
+    }
+
+    /// Reserve a path for an archived recording (the audio directory is created on demand)
+    fn new_audio_path(&self) -> Result<PathBuf, String> {
+        fs::create_dir_all(&self.audio_dir)
+            .map_err(|e| format!("Failed to create audio directory: {}", e))?;
+        let name = format!("{}-{}.wav", Utc::now().format("%Y%m%d-%H%M%S"), Self::generate_id());
+        Ok(self.audio_dir.join(name))
     }
 
     /// Generate a unique ID for a log entry
@@ -79,23 +139,22 @@ impl LogManager {
     }
 
     /// Add a new log entry
-    pub fn add_entry(
-        &self,
-        raw_text: String,
-        refined_text: Option<String>,
-        audio_duration_secs: Option<f32>,
-        llm_used: bool,
-        prompt_preset: Option<String>,
-    ) -> Result<LogEntry, String> {
+    pub fn add_entry(&self, new_entry: NewLogEntry) -> Result<LogEntry, String> {
         let now = Utc::now();
         let entry = LogEntry {
             id: Self::generate_id(),
             timestamp: now,
-            raw_text,
-            refined_text,
-            audio_duration_secs,
-            llm_used,
-            prompt_preset,
+            raw_text: new_entry.raw_text,
+            refined_text: new_entry.refined_text,
+            audio_duration_secs: new_entry.audio_duration_secs,
+            llm_used: new_entry.llm_used,
+            prompt_preset: new_entry.prompt_preset,
+            audio_path: new_entry.audio_path,
+            source_file: new_entry.source_file,
+            language: new_entry.language,
+            translated: new_entry.translated,
+            segments: new_entry.segments,
+            filtered: new_entry.filtered,
         };
 
         // Load existing logs for today, add the new entry, and save
@@ -107,6 +166,33 @@ impl LogManager {
         Ok(entry)
     }
 
+    /// Add a new log entry together with its archived recording (`(samples, sample_rate)`).
+    /// The WAV is written right before the entry and removed again if the entry
+    /// cannot be saved, so every archive file belongs to an entry.
+    pub fn add_entry_with_audio(
+        &self,
+        mut new_entry: NewLogEntry,
+        audio: Option<(&[f32], u32)>,
+    ) -> Result<LogEntry, String> {
+        if let Some((samples, sample_rate)) = audio {
+            // A failed archive does not prevent logging the transcription
+            match self.new_audio_path() {
+                Ok(path) => match write_wav(&path, samples, sample_rate) {
+                    Ok(()) => new_entry.audio_path = Some(path.to_string_lossy().into_owned()),
+                    Err(e) => tracing::warn!("Failed to archive recording: {}", e),
+                },
+                Err(e) => tracing::warn!("Failed to prepare audio archive: {}", e),
+            }
+        }
+
+        let audio_path = new_entry.audio_path.clone();
+        self.add_entry(new_entry).inspect_err(|_| {
+            if let Some(path) = &audio_path {
+                let _ = fs::remove_file(path);
+            }
+        })
+    }
+
     /// Get all log entries for a specific date
     pub fn get_logs_for_date(&self, year: i32, month: u32, day: u32) -> Vec<LogEntry> {
         use chrono::NaiveDate;
@@ -170,15 +256,16 @@ impl LogManager {
 
         for path in log_files {
             if let Ok(content) = fs::read_to_string(&path) {
-                if let Ok(mut logs) = serde_json::from_str::<Vec<LogEntry>>(&content) {
-                    let original_len = logs.len();
-                    logs.retain(|entry| entry.id != id);
+                if let Ok(logs) = serde_json::from_str::<Vec<LogEntry>>(&content) {
+                    let (removed, logs): (Vec<LogEntry>, Vec<LogEntry>) =
+                        logs.into_iter().partition(|entry| entry.id == id);
 
-                    if logs.len() < original_len {
+                    if !removed.is_empty() {
                         // Entry was found and removed
                         let content = serde_json::to_string_pretty(&logs)
                             .map_err(|e| format!("Failed to serialize logs: {}", e))?;
                         fs::write(&path, content).map_err(|e| format!("Failed to write log file: {}", e))?;
+                        remove_audio_files(&removed);
                         tracing::info!("Deleted log entry: {}", id);
                         return Ok(true);
                     }
@@ -233,11 +320,21 @@ impl LogManager {
             if let Ok(content) = fs::read_to_string(&path) {
                 if let Ok(logs) = serde_json::from_str::<Vec<LogEntry>>(&content) {
                     deleted_count += logs.len();
+                    remove_audio_files(&logs);
                 }
             }
             fs::remove_file(&path).map_err(|e| format!("Failed to delete log file: {}", e))?;
         }
 
+        // Also remove archived recordings that no entry refers to
+        if let Ok(entries) = fs::read_dir(&self.audio_dir) {
+            for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+                if path.extension().is_some_and(|ext| ext == "wav") {
+                    let _ = fs::remove_file(&path);
+                }
+            }
+        }
+
         tracing::info!("Deleted all log entries: {} entries", deleted_count);
         Ok(deleted_count)
     }
@@ -249,10 +346,48 @@ impl Default for LogManager {
     }
 }
 
+#[cfg(test)]
+impl LogManager {
+    /// Log manager storing everything under `dir`
+    pub(crate) fn in_dir(dir: &std::path::Path) -> Self {
+        let log_dir = dir.join("logs");
+        fs::create_dir_all(&log_dir).unwrap();
+        Self {
+            log_dir,
+            audio_dir: dir.join("audio"),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("log-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_add_entry_with_audio() {
+        let dir = temp_dir("audio");
+        let log_manager = LogManager::in_dir(&dir);
+        let audio = Some((&[0.0, 0.5][..], 16000));
+
+        // The archive is removed again when the entry cannot be saved
+        fs::remove_dir_all(dir.join("logs")).unwrap();
+        assert!(log_manager.add_entry_with_audio(NewLogEntry::default(), audio).is_err());
+        assert_eq!(fs::read_dir(dir.join("audio")).unwrap().count(), 0);
+
+        fs::create_dir_all(dir.join("logs")).unwrap();
+        let entry = log_manager.add_entry_with_audio(NewLogEntry::default(), audio).unwrap();
+        let path = PathBuf::from(entry.audio_path.unwrap());
+        assert!(path.starts_with(dir.join("audio")) && path.exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
     #[test]
     fn test_generate_id() {
         let id1 = LogManager::generate_id();
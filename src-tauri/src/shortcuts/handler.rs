@@ -194,6 +194,33 @@ impl ShortcutHandler {
         Ok(())
     }
 
+    /// Register a shortcut that only runs `on_press`, without the recording-toggle event
+    pub fn register_action<F>(app: &AppHandle, shortcut_str: &str, on_press: F) -> Result<(), Box<dyn std::error::Error>>
+    where
+        F: Fn(&AppHandle) + Send + Sync + 'static,
+    {
+        let shortcut = parse_shortcut(shortcut_str)?;
+        let shortcut_display = shortcut_str.to_string();
+
+        app.global_shortcut().on_shortcut(shortcut, move |app, _shortcut, event| {
+            if event.state == ShortcutState::Pressed {
+                tracing::info!("Global shortcut triggered: {}", shortcut_display);
+                on_press(app);
+            }
+        })?;
+        tracing::info!("Global shortcut registered: {}", shortcut_str);
+
+        Ok(())
+    }
+
+    /// Unregister a shortcut registered with `register_action`
+    pub fn unregister(app: &AppHandle, shortcut_str: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let shortcut = parse_shortcut(shortcut_str)?;
+        app.global_shortcut().unregister(shortcut)?;
+        tracing::info!("Global shortcut unregistered: {}", shortcut_str);
+        Ok(())
+    }
+
     pub fn update_shortcut<F>(
         app: &AppHandle,
         _old_shortcut_str: &str,
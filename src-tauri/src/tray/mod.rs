@@ -1,4 +1,4 @@
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
 use std::sync::Arc;
 use tauri::{
     image::Image,
@@ -63,14 +63,45 @@ fn create_recording_icon() -> Vec<u8> {
     create_status_icon([220, 53, 69]) // Red
 }
 
+/// Create a clipping indicator icon (orange dot)
+fn create_clipping_icon() -> Vec<u8> {
+    create_status_icon([253, 126, 20]) // Orange
+}
+
+/// Create a silent input indicator icon (gray dot)
+fn create_silent_icon() -> Vec<u8> {
+    create_status_icon([108, 117, 125]) // Gray
+}
+
+/// Create a hands-free listening indicator icon (green dot)
+fn create_listening_icon() -> Vec<u8> {
+    create_status_icon([40, 167, 69]) // Green
+}
+
 /// Create an LLM processing indicator icon (purple/blue dot)
 fn create_llm_processing_icon() -> Vec<u8> {
     create_status_icon([102, 126, 234]) // Purple/Blue (#667eea)
 }
 
+// Input level states shown while recording
+const INPUT_LEVEL_NORMAL: u8 = 0;
+const INPUT_LEVEL_SILENT: u8 = 1;
+const INPUT_LEVEL_CLIPPING: u8 = 2;
+
+/// Subset of the `audio-level` event payload used by the tray
+#[derive(serde::Deserialize)]
+struct AudioLevelPayload {
+    rms_db: f32,
+    clipping: bool,
+    silent: bool,
+}
+
 pub struct TrayManager {
     is_recording: Arc<AtomicBool>,
     is_llm_processing: Arc<AtomicBool>,
+    /// Hands-free mode is waiting for speech
+    is_listening: Arc<AtomicBool>,
+    input_level: Arc<AtomicU8>,
 }
 
 impl TrayManager {
@@ -78,12 +109,16 @@ impl TrayManager {
         Self {
             is_recording: Arc::new(AtomicBool::new(false)),
             is_llm_processing: Arc::new(AtomicBool::new(false)),
+            is_listening: Arc::new(AtomicBool::new(false)),
+            input_level: Arc::new(AtomicU8::new(INPUT_LEVEL_NORMAL)),
         }
     }
 
     pub fn setup(&self, app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
         let is_recording = self.is_recording.clone();
         let is_llm_processing = self.is_llm_processing.clone();
+        let is_listening = self.is_listening.clone();
+        let input_level = self.input_level.clone();
 
         // Create menu items
         let show_item = MenuItemBuilder::with_id("show", "ウィンドウを表示").build(app)?;
@@ -133,24 +168,33 @@ impl TrayManager {
         let app_handle = app.clone();
         let is_recording_clone = is_recording.clone();
         let is_llm_processing_clone = is_llm_processing.clone();
+        let is_listening_clone = is_listening.clone();
+        let input_level_clone = input_level.clone();
         app.listen("recording-started", move |_| {
             is_recording_clone.store(true, Ordering::SeqCst);
+            input_level_clone.store(INPUT_LEVEL_NORMAL, Ordering::SeqCst);
             update_tray_icon(
                 &app_handle,
                 is_recording_clone.load(Ordering::SeqCst),
                 is_llm_processing_clone.load(Ordering::SeqCst),
+                is_listening_clone.load(Ordering::SeqCst),
+                input_level_clone.load(Ordering::SeqCst),
             );
         });
 
         let app_handle = app.clone();
         let is_recording_clone = is_recording.clone();
         let is_llm_processing_clone = is_llm_processing.clone();
+        let is_listening_clone = is_listening.clone();
+        let input_level_clone = input_level.clone();
         app.listen("recording-stopped", move |_| {
             is_recording_clone.store(false, Ordering::SeqCst);
             update_tray_icon(
                 &app_handle,
                 is_recording_clone.load(Ordering::SeqCst),
                 is_llm_processing_clone.load(Ordering::SeqCst),
+                is_listening_clone.load(Ordering::SeqCst),
+                input_level_clone.load(Ordering::SeqCst),
             );
         });
 
@@ -158,49 +202,134 @@ impl TrayManager {
         let app_handle = app.clone();
         let is_recording_clone = is_recording.clone();
         let is_llm_processing_clone = is_llm_processing.clone();
+        let is_listening_clone = is_listening.clone();
+        let input_level_clone = input_level.clone();
         app.listen("llm-refinement-started", move |_| {
             is_llm_processing_clone.store(true, Ordering::SeqCst);
             update_tray_icon(
                 &app_handle,
                 is_recording_clone.load(Ordering::SeqCst),
                 is_llm_processing_clone.load(Ordering::SeqCst),
+                is_listening_clone.load(Ordering::SeqCst),
+                input_level_clone.load(Ordering::SeqCst),
             );
         });
 
         let app_handle = app.clone();
         let is_recording_clone = is_recording.clone();
         let is_llm_processing_clone = is_llm_processing.clone();
+        let is_listening_clone = is_listening.clone();
+        let input_level_clone = input_level.clone();
         app.listen("llm-refinement-complete", move |_| {
             is_llm_processing_clone.store(false, Ordering::SeqCst);
             update_tray_icon(
                 &app_handle,
                 is_recording_clone.load(Ordering::SeqCst),
                 is_llm_processing_clone.load(Ordering::SeqCst),
+                is_listening_clone.load(Ordering::SeqCst),
+                input_level_clone.load(Ordering::SeqCst),
             );
         });
 
         let app_handle = app.clone();
         let is_recording_clone = is_recording.clone();
         let is_llm_processing_clone = is_llm_processing.clone();
+        let is_listening_clone = is_listening.clone();
+        let input_level_clone = input_level.clone();
         app.listen("llm-refinement-failed", move |_| {
             is_llm_processing_clone.store(false, Ordering::SeqCst);
             update_tray_icon(
                 &app_handle,
                 is_recording_clone.load(Ordering::SeqCst),
                 is_llm_processing_clone.load(Ordering::SeqCst),
+                is_listening_clone.load(Ordering::SeqCst),
+                input_level_clone.load(Ordering::SeqCst),
+            );
+        });
+
+        // Listen for hands-free mode changes
+        let app_handle = app.clone();
+        let is_recording_clone = is_recording.clone();
+        let is_llm_processing_clone = is_llm_processing.clone();
+        let is_listening_clone = is_listening.clone();
+        let input_level_clone = input_level.clone();
+        app.listen("hands-free-started", move |_| {
+            is_listening_clone.store(true, Ordering::SeqCst);
+            update_tray_icon(
+                &app_handle,
+                is_recording_clone.load(Ordering::SeqCst),
+                is_llm_processing_clone.load(Ordering::SeqCst),
+                is_listening_clone.load(Ordering::SeqCst),
+                input_level_clone.load(Ordering::SeqCst),
             );
         });
 
+        let app_handle = app.clone();
+        let is_recording_clone = is_recording.clone();
+        let is_llm_processing_clone = is_llm_processing.clone();
+        let is_listening_clone = is_listening.clone();
+        let input_level_clone = input_level.clone();
+        app.listen("hands-free-stopped", move |_| {
+            is_listening_clone.store(false, Ordering::SeqCst);
+            update_tray_icon(
+                &app_handle,
+                is_recording_clone.load(Ordering::SeqCst),
+                is_llm_processing_clone.load(Ordering::SeqCst),
+                is_listening_clone.load(Ordering::SeqCst),
+                input_level_clone.load(Ordering::SeqCst),
+            );
+        });
+
+        // Reflect the live input level while recording
+        let app_handle = app.clone();
+        let is_recording_clone = is_recording.clone();
+        let is_llm_processing_clone = is_llm_processing.clone();
+        let is_listening_clone = is_listening.clone();
+        let input_level_clone = input_level.clone();
+        app.listen("audio-level", move |event| {
+            if !is_recording_clone.load(Ordering::SeqCst) {
+                return;
+            }
+            let Ok(level) = serde_json::from_str::<AudioLevelPayload>(event.payload()) else {
+                return;
+            };
+            let new_state = if level.clipping {
+                INPUT_LEVEL_CLIPPING
+            } else if level.silent {
+                INPUT_LEVEL_SILENT
+            } else {
+                INPUT_LEVEL_NORMAL
+            };
+            // Only redraw the icon when the level state changes
+            if input_level_clone.swap(new_state, Ordering::SeqCst) != new_state {
+                update_tray_icon(
+                    &app_handle,
+                    true,
+                    is_llm_processing_clone.load(Ordering::SeqCst),
+                    is_listening_clone.load(Ordering::SeqCst),
+                    new_state,
+                );
+            }
+            if let Some(tray) = app_handle.tray_by_id(TRAY_ID) {
+                let tooltip = format!("HushWhisper - 録音中... (入力 {:.0} dB)", level.rms_db);
+                let _ = tray.set_tooltip(Some(tooltip.as_str()));
+            }
+        });
+
         tracing::info!("System tray initialized");
         Ok(())
     }
 }
 
-fn update_tray_icon(app: &AppHandle, is_recording: bool, is_llm_processing: bool) {
+fn update_tray_icon(app: &AppHandle, is_recording: bool, is_llm_processing: bool, is_listening: bool, input_level: u8) {
     if let Some(tray) = app.tray_by_id(TRAY_ID) {
         let result = if is_recording {
-            // Recording: red circle icon (highest priority)
-            let icon_data = create_recording_icon();
+            // Recording: red circle icon (highest priority), orange/gray for clipping/silent input
+            let icon_data = match input_level {
+                INPUT_LEVEL_CLIPPING => create_clipping_icon(),
+                INPUT_LEVEL_SILENT => create_silent_icon(),
+                _ => create_recording_icon(),
+            };
             let icon = Image::new_owned(icon_data, 32, 32);
             let _ = tray.set_icon(Some(icon));
             tray.set_tooltip(Some("HushWhisper - 録音中..."))
@@ -210,6 +339,12 @@ fn update_tray_icon(app: &AppHandle, is_recording: bool, is_llm_processing: bool
             let icon = Image::new_owned(icon_data, 32, 32);
             let _ = tray.set_icon(Some(icon));
             tray.set_tooltip(Some("HushWhisper - LLM処理中..."))
+        } else if is_listening {
+            // Hands-free mode: green circle icon
+            let icon_data = create_listening_icon();
+            let icon = Image::new_owned(icon_data, 32, 32);
+            let _ = tray.set_icon(Some(icon));
+            tray.set_tooltip(Some("HushWhisper - ハンズフリー待機中"))
         } else {
             // Normal: default icon
             match Image::from_bytes(ICON_NORMAL) {
@@ -0,0 +1,60 @@
+use serde::Serialize;
+
+/// Compute backend whisper.cpp runs on, selected by the cargo features
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum WhisperBackend {
+    Cpu,
+    OpenBlas,
+    Cuda,
+    Vulkan,
+}
+
+impl WhisperBackend {
+    /// Backend compiled into this build. GPU backends take precedence over OpenBLAS.
+    pub fn compiled() -> Self {
+        if cfg!(feature = "cuda") {
+            Self::Cuda
+        } else if cfg!(feature = "vulkan") {
+            Self::Vulkan
+        } else if cfg!(feature = "openblas") {
+            Self::OpenBlas
+        } else {
+            Self::Cpu
+        }
+    }
+
+    pub fn is_gpu(self) -> bool {
+        matches!(self, Self::Cuda | Self::Vulkan)
+    }
+
+    /// Backend used for a model loaded with the given GPU setting
+    pub fn resolve(compiled: Self, use_gpu: bool) -> Self {
+        if compiled.is_gpu() && !use_gpu {
+            Self::Cpu
+        } else {
+            compiled
+        }
+    }
+}
+
+/// Backends reported to the frontend
+#[derive(Debug, Clone, Serialize)]
+pub struct WhisperBackendInfo {
+    /// Backend this build supports
+    pub compiled: WhisperBackend,
+    /// Backend of the loaded model (None = no model loaded)
+    pub active: Option<WhisperBackend>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_backend() {
+        assert_eq!(WhisperBackend::resolve(WhisperBackend::Cuda, true), WhisperBackend::Cuda);
+        assert_eq!(WhisperBackend::resolve(WhisperBackend::Vulkan, false), WhisperBackend::Cpu);
+        assert_eq!(WhisperBackend::resolve(WhisperBackend::OpenBlas, false), WhisperBackend::OpenBlas);
+        assert_eq!(WhisperBackend::resolve(WhisperBackend::Cpu, true), WhisperBackend::Cpu);
+    }
+}
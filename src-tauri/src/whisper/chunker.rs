@@ -0,0 +1,185 @@
+use std::ops::Range;
+
+use super::streaming::split_units;
+use super::transcribe::TranscriptionResult;
+
+/// Sample rate of the audio passed to Whisper
+const SAMPLE_RATE: usize = 16000;
+/// Longest chunk passed to Whisper, leaving headroom below its 30-second window
+const MAX_CHUNK_SAMPLES: usize = 25 * SAMPLE_RATE;
+/// Chunks are not cut shorter than this unless the audio ends
+const MIN_CHUNK_SAMPLES: usize = 10 * SAMPLE_RATE;
+/// Audio repeated at the start of the next chunk when no pause was found by the VAD
+const OVERLAP_SAMPLES: usize = SAMPLE_RATE;
+/// Frame in which the quietest cut point is searched (100ms)
+const FRAME_SAMPLES: usize = 1600;
+/// Shorter repeats at a chunk boundary are taken as natural speech, not duplicates
+const MIN_OVERLAP_UNITS: usize = 2;
+/// How far back into the previous text duplicates are searched
+const MAX_OVERLAP_UNITS: usize = 40;
+
+/// Split audio into chunks Whisper can transcribe in one window.
+///
+/// Cuts at the last of `boundaries` (pauses between VAD segments) that keeps the
+/// chunk short enough. Without one, cuts at the quietest frame and lets the next
+/// chunk start `OVERLAP_SAMPLES` earlier, so words at the cut are not lost.
+pub fn plan_chunks(audio: &[f32], boundaries: &[usize]) -> Vec<Range<usize>> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+
+    while audio.len() - start > MAX_CHUNK_SAMPLES {
+        let earliest = start + MIN_CHUNK_SAMPLES;
+        let latest = start + MAX_CHUNK_SAMPLES;
+        match boundaries.iter().rev().find(|&&b| b > earliest && b <= latest) {
+            Some(&cut) => {
+                chunks.push(start..cut);
+                start = cut;
+            }
+            None => {
+                let cut = quietest_point(audio, earliest..latest);
+                chunks.push(start..cut);
+                start = cut - OVERLAP_SAMPLES;
+            }
+        }
+    }
+    chunks.push(start..audio.len());
+    chunks
+}
+
+/// Center of the frame with the lowest energy in `range`
+fn quietest_point(audio: &[f32], range: Range<usize>) -> usize {
+    let energy = |start: usize| {
+        audio[start..(start + FRAME_SAMPLES).min(range.end)]
+            .iter()
+            .map(|&s| s * s)
+            .sum::<f32>()
+    };
+    let quietest = (range.start..range.end)
+        .step_by(FRAME_SAMPLES)
+        .min_by(|&a, &b| energy(a).total_cmp(&energy(b)))
+        .unwrap_or(range.start);
+    (quietest + FRAME_SAMPLES / 2).min(range.end)
+}
+
+/// Append the transcription of a chunk that starts `offset_secs` into the audio.
+/// Text at the start of the chunk that repeats the end of `result` (overlapping
+/// audio, or Whisper continuing the prompt) is dropped.
+pub fn append_chunk(result: &mut TranscriptionResult, mut chunk: TranscriptionResult, offset_secs: f32) {
+    chunk.map_times(|secs| secs + offset_secs);
+
+    let previous: Vec<String> = result.segments.iter().flat_map(|s| split_units(&s.text)).collect();
+    let next: Vec<String> = chunk.segments.iter().flat_map(|s| split_units(&s.text)).collect();
+    let previous = &previous[previous.len().saturating_sub(MAX_OVERLAP_UNITS)..];
+    let duplicated = (MIN_OVERLAP_UNITS..=previous.len().min(next.len()))
+        .rev()
+        .find(|&n| {
+            previous[previous.len() - n..]
+                .iter()
+                .zip(&next[..n])
+                .all(|(a, b)| a.trim() == b.trim())
+        })
+        .unwrap_or(0);
+    if duplicated > 0 {
+        tracing::debug!("Dropping {} duplicated units at a chunk boundary", duplicated);
+    }
+
+    let mut remaining = duplicated;
+    for mut segment in chunk.segments {
+        if remaining > 0 {
+            let units = split_units(&segment.text);
+            if units.len() <= remaining {
+                remaining -= units.len();
+                continue;
+            }
+            segment.text = units[remaining..].concat();
+            remaining = 0;
+        }
+        result.segments.push(segment);
+    }
+    if result.language.is_none() {
+        result.language = chunk.language;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::whisper::TranscriptSegment;
+
+    const SECOND: usize = SAMPLE_RATE;
+
+    fn transcription(segments: &[(f32, f32, &str)]) -> TranscriptionResult {
+        TranscriptionResult {
+            text: String::new(),
+            segments: segments
+                .iter()
+                .map(|&(start_secs, end_secs, text)| TranscriptSegment {
+                    start_secs,
+                    end_secs,
+                    text: text.to_string(),
+                    avg_token_prob: 0.9,
+                    no_speech_prob: 0.0,
+                })
+                .collect(),
+            language: Some("ja".to_string()),
+        }
+    }
+
+    fn texts(result: &TranscriptionResult) -> Vec<&str> {
+        result.segments.iter().map(|s| s.text.as_str()).collect()
+    }
+
+    #[test]
+    fn test_short_audio_is_one_chunk() {
+        let audio = vec![0.1; 20 * SECOND];
+        assert_eq!(plan_chunks(&audio, &[5 * SECOND]), vec![0..20 * SECOND]);
+        assert_eq!(plan_chunks(&[], &[]), vec![0..0]);
+    }
+
+    #[test]
+    fn test_chunks_cut_at_last_boundary_in_range() {
+        let audio = vec![0.1; 60 * SECOND];
+        // 8s is too early for the first cut, 24s is the last one within 25s
+        let boundaries = [8 * SECOND, 20 * SECOND, 24 * SECOND, 40 * SECOND, 55 * SECOND];
+        assert_eq!(
+            plan_chunks(&audio, &boundaries),
+            vec![0..24 * SECOND, 24 * SECOND..40 * SECOND, 40 * SECOND..60 * SECOND]
+        );
+    }
+
+    #[test]
+    fn test_chunks_cut_at_quietest_point_with_overlap() {
+        let mut audio = vec![0.3; 40 * SECOND];
+        // A short pause at 18s-18.2s that the VAD did not report
+        audio[18 * SECOND..18 * SECOND + 3200].fill(0.0);
+
+        let chunks = plan_chunks(&audio, &[]);
+        assert_eq!(chunks.len(), 2);
+        let cut = chunks[0].end;
+        assert!((18 * SECOND..18 * SECOND + 3200).contains(&cut), "{}", cut);
+        assert_eq!(chunks[1], cut - OVERLAP_SAMPLES..40 * SECOND);
+    }
+
+    #[test]
+    fn test_append_chunk_drops_duplicated_text() {
+        let mut result = transcription(&[]);
+        append_chunk(&mut result, transcription(&[(0.0, 5.0, "今日は晴れです。"), (5.0, 9.0, "明日は")]), 0.0);
+        // The overlapping audio repeats "明日は" at the start of the next chunk
+        append_chunk(&mut result, transcription(&[(0.0, 3.0, "明日は雨です。")]), 8.0);
+
+        assert_eq!(texts(&result), vec!["今日は晴れです。", "明日は", "雨です。"]);
+        assert_eq!((result.segments[2].start_secs, result.segments[2].end_secs), (8.0, 11.0));
+        assert_eq!(result.language.as_deref(), Some("ja"));
+    }
+
+    #[test]
+    fn test_append_chunk_keeps_distinct_text() {
+        let mut result = transcription(&[(0.0, 4.0, " See you.")]);
+        append_chunk(&mut result, transcription(&[(0.0, 2.0, " You know,"), (2.0, 4.0, " it works.")]), 4.0);
+        // A single repeated word is not a duplicate
+        assert_eq!(texts(&result), vec![" See you.", " You know,", " it works."]);
+
+        append_chunk(&mut result, transcription(&[(0.0, 2.0, " it works."), (2.0, 3.0, " Bye.")]), 8.0);
+        assert_eq!(texts(&result), vec![" See you.", " You know,", " it works.", " Bye."]);
+    }
+}
@@ -0,0 +1,239 @@
+use serde::{Deserialize, Serialize};
+
+use super::streaming::split_units;
+use super::transcribe::{join_segment_texts, TranscriptSegment, TranscriptionResult};
+use crate::config::OutputFilterSettings;
+
+/// Longest phrase (in words or characters) checked for runaway repetition
+const MAX_REPEAT_UNITS: usize = 16;
+
+/// Why the filter removed text
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FilterReason {
+    /// The segment matched a blocklisted phrase
+    Blocklist,
+    /// A phrase was repeated more than `max_repeats` times
+    Repetition,
+    /// The segment probably contains no speech
+    NoSpeech,
+}
+
+/// Text removed by the filter, kept in the log for auditing
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FilterRemoval {
+    pub reason: FilterReason,
+    pub text: String,
+    /// Start of the segment the text was removed from (seconds)
+    pub start_secs: f32,
+}
+
+/// Lowercase letters and digits only, so punctuation and spacing do not matter
+fn normalize(text: &str) -> String {
+    text.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn matches_phrase(normalized: &str, phrase: &str) -> bool {
+    match phrase.strip_suffix('*') {
+        Some(prefix) => {
+            let prefix = normalize(prefix);
+            !prefix.is_empty() && normalized.starts_with(&prefix)
+        }
+        None => !normalized.is_empty() && normalized == normalize(phrase),
+    }
+}
+
+/// Cut runs of a phrase repeated more than `max_repeats` times back to `max_repeats`.
+/// Returns the units kept and the text removed.
+fn collapse_repeats(units: &[String], max_repeats: usize) -> (Vec<String>, String) {
+    let same = |a: &[String], b: &[String]| a.iter().zip(b).all(|(a, b)| a.trim() == b.trim());
+    let mut kept = Vec::with_capacity(units.len());
+    let mut removed = String::new();
+    let mut i = 0;
+
+    'units: while i < units.len() {
+        let longest = MAX_REPEAT_UNITS.min((units.len() - i) / (max_repeats + 1));
+        for n in 1..=longest {
+            let phrase = &units[i..i + n];
+            let mut repeats = 1;
+            while i + (repeats + 1) * n <= units.len() && same(phrase, &units[i + repeats * n..i + (repeats + 1) * n]) {
+                repeats += 1;
+            }
+            if repeats > max_repeats {
+                kept.extend_from_slice(&units[i..i + max_repeats * n]);
+                removed.push_str(&units[i + max_repeats * n..i + repeats * n].concat());
+                i += repeats * n;
+                continue 'units;
+            }
+        }
+        kept.push(units[i].clone());
+        i += 1;
+    }
+    (kept, removed)
+}
+
+/// Remove hallucinated text from a transcription and return what was removed.
+/// `text` is rebuilt from the remaining segments when anything changed.
+pub fn filter_transcription(
+    result: &mut TranscriptionResult,
+    settings: &OutputFilterSettings,
+    insert_newline: bool,
+) -> Vec<FilterRemoval> {
+    if !settings.enabled {
+        return Vec::new();
+    }
+
+    let mut removals = Vec::new();
+    let mut remove = |segment: &TranscriptSegment, reason: FilterReason, text: String| {
+        tracing::info!("Filtered {:?} at {:.1}s: {:?}", reason, segment.start_secs, text.trim());
+        removals.push(FilterRemoval {
+            reason,
+            text: text.trim().to_string(),
+            start_secs: segment.start_secs,
+        });
+    };
+
+    let max_repeats = settings.max_repeats as usize;
+    let mut kept: Vec<TranscriptSegment> = Vec::with_capacity(result.segments.len());
+    for mut segment in std::mem::take(&mut result.segments) {
+        if segment.no_speech_prob > settings.no_speech_prob {
+            let text = segment.text.clone();
+            remove(&segment, FilterReason::NoSpeech, text);
+            continue;
+        }
+
+        let normalized = normalize(&segment.text);
+        if settings.blocklist.iter().any(|phrase| matches_phrase(&normalized, phrase)) {
+            let text = segment.text.clone();
+            remove(&segment, FilterReason::Blocklist, text);
+            continue;
+        }
+
+        if max_repeats > 0 {
+            // The same segment over and over (Whisper stuck in a loop)
+            let repeats = kept
+                .iter()
+                .rev()
+                .take_while(|previous| normalize(&previous.text) == normalized)
+                .count();
+            if !normalized.is_empty() && repeats >= max_repeats {
+                let text = segment.text.clone();
+                remove(&segment, FilterReason::Repetition, text);
+                continue;
+            }
+
+            // A phrase repeated within the segment
+            let (units, removed) = collapse_repeats(&split_units(&segment.text), max_repeats);
+            if !removed.is_empty() {
+                remove(&segment, FilterReason::Repetition, removed);
+                segment.text = units.concat();
+            }
+        }
+        kept.push(segment);
+    }
+    result.segments = kept;
+
+    if !removals.is_empty() {
+        result.text = join_segment_texts(&result.segments, insert_newline);
+    }
+    removals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(text: &str, no_speech_prob: f32) -> TranscriptSegment {
+        TranscriptSegment {
+            start_secs: 0.0,
+            end_secs: 1.0,
+            text: text.to_string(),
+            avg_token_prob: 0.9,
+            no_speech_prob,
+        }
+    }
+
+    fn transcription(texts: &[&str]) -> TranscriptionResult {
+        TranscriptionResult {
+            text: texts.concat(),
+            segments: texts.iter().map(|text| segment(text, 0.0)).collect(),
+            language: None,
+        }
+    }
+
+    fn reasons(removals: &[FilterRemoval]) -> Vec<FilterReason> {
+        removals.iter().map(|removal| removal.reason).collect()
+    }
+
+    #[test]
+    fn test_blocklist_removes_hallucinated_segments() {
+        let settings = OutputFilterSettings::default();
+        let mut result = transcription(&["今日は晴れです。", "ご視聴ありがとうございました。", "字幕by ひかり"]);
+
+        let removals = filter_transcription(&mut result, &settings, false);
+        assert_eq!(result.text, "今日は晴れです。");
+        assert_eq!(reasons(&removals), vec![FilterReason::Blocklist; 2]);
+        assert_eq!(removals[0].text, "ご視聴ありがとうございました。");
+    }
+
+    #[test]
+    fn test_blocklist_matches_whole_phrase_only() {
+        assert!(matches_phrase(&normalize(" Thank you for watching!"), "Thank you for watching"));
+        assert!(!matches_phrase(&normalize("皆さん、ご視聴ありがとうございました。また明日"), "ご視聴ありがとうございました"));
+        assert!(matches_phrase(&normalize("字幕作成：山田"), "字幕作成*"));
+        assert!(!matches_phrase(&normalize("。"), "*"));
+    }
+
+    #[test]
+    fn test_collapse_repeated_phrase() {
+        let settings = OutputFilterSettings::default();
+        let mut result = transcription(&["はい、わかりました。わかりました。わかりました。わかりました。わかりました。"]);
+
+        let removals = filter_transcription(&mut result, &settings, false);
+        assert_eq!(result.text, "はい、わかりました。わかりました。わかりました。");
+        assert_eq!(removals[0].reason, FilterReason::Repetition);
+        assert_eq!(removals[0].text, "わかりました。わかりました。");
+
+        // Short natural repetition is kept
+        let mut result = transcription(&[" yes yes, it is"]);
+        assert!(filter_transcription(&mut result, &settings, false).is_empty());
+        assert_eq!(result.text, " yes yes, it is");
+    }
+
+    #[test]
+    fn test_repeated_segments_are_dropped() {
+        let settings = OutputFilterSettings::default();
+        let mut result = transcription(&["よろしく。", "以上です。", "以上です。", "以上です。", "以上です。"]);
+
+        let removals = filter_transcription(&mut result, &settings, true);
+        assert_eq!(result.text, "よろしく。\n以上です。\n以上です。\n以上です。");
+        assert_eq!(reasons(&removals), vec![FilterReason::Repetition]);
+    }
+
+    #[test]
+    fn test_no_speech_segments_are_dropped() {
+        let settings = OutputFilterSettings::default();
+        let mut result = TranscriptionResult {
+            text: String::new(),
+            segments: vec![segment("話した内容", 0.1), segment("ノイズ", 0.95)],
+            language: None,
+        };
+
+        let removals = filter_transcription(&mut result, &settings, false);
+        assert_eq!(result.text, "話した内容");
+        assert_eq!(reasons(&removals), vec![FilterReason::NoSpeech]);
+    }
+
+    #[test]
+    fn test_disabled_filter_keeps_everything() {
+        let settings = OutputFilterSettings {
+            enabled: false,
+            ..OutputFilterSettings::default()
+        };
+        let mut result = transcription(&["ご視聴ありがとうございました"]);
+        assert!(filter_transcription(&mut result, &settings, false).is_empty());
+        assert_eq!(result.segments.len(), 1);
+    }
+}
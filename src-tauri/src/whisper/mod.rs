@@ -1,3 +1,11 @@
+pub mod backend;
+pub mod chunker;
+pub mod filter;
+pub mod prompt;
+pub mod streaming;
 pub mod transcribe;
 
-pub use transcribe::WhisperTranscriber;
+pub use backend::{WhisperBackend, WhisperBackendInfo};
+pub use filter::{filter_transcription, FilterRemoval};
+pub use streaming::LiveTranscript;
+pub use transcribe::{TranscriptSegment, TranscriptionResult, WhisperTranscriber};
@@ -0,0 +1,129 @@
+use crate::config::WhisperPrompt;
+
+/// Whisper reads at most half of its 448-token text context as the prompt
+pub const MAX_PROMPT_TOKENS: usize = 224;
+
+/// Join the initial prompt and the vocabulary into the text passed to Whisper.
+/// The vocabulary goes last, so it survives when a long prompt is shortened.
+pub fn build_prompt(prompt: &WhisperPrompt) -> String {
+    let vocabulary = prompt.vocabulary.join(", ");
+    match (prompt.initial_prompt.is_empty(), vocabulary.is_empty()) {
+        (_, true) => prompt.initial_prompt.clone(),
+        (true, false) => vocabulary,
+        (false, false) => format!("{} {}", prompt.initial_prompt, vocabulary),
+    }
+}
+
+/// Prompt for a chunk of a long recording: the initial prompt and vocabulary, then the
+/// text transcribed before the chunk, so Whisper continues in the same style and spelling.
+/// The previous text goes last, right before the audio, and is shortened first when the
+/// prompt is too long.
+pub fn chunk_prompt(
+    prompt: &WhisperPrompt,
+    previous_text: &str,
+    max_tokens: usize,
+    count_tokens: impl Fn(&str) -> usize,
+) -> String {
+    let head = build_prompt(prompt);
+    // Without room for any previous text, shorten the prompt itself as usual
+    if count_tokens(&head) >= max_tokens {
+        return fit_prompt(&head, max_tokens, count_tokens);
+    }
+    fit_tail(&head, previous_text.trim(), max_tokens, count_tokens)
+}
+
+/// Drop text from the start of `text` until `count_tokens` reports at most
+/// `max_tokens`, keeping the end as Whisper itself does
+pub fn fit_prompt(text: &str, max_tokens: usize, count_tokens: impl Fn(&str) -> usize) -> String {
+    fit_tail("", text, max_tokens, count_tokens)
+}
+
+/// `head` followed by the longest end of `tail` that fits in `max_tokens`
+fn fit_tail(head: &str, tail: &str, max_tokens: usize, count_tokens: impl Fn(&str) -> usize) -> String {
+    let join = |tail: &str| match (head.is_empty(), tail.is_empty()) {
+        (_, true) => head.to_string(),
+        (true, false) => tail.to_string(),
+        (false, false) => format!("{} {}", head, tail),
+    };
+    let joined = join(tail);
+    if count_tokens(&joined) <= max_tokens {
+        return joined;
+    }
+
+    // Binary search for the longest suffix (on a char boundary) that fits
+    let starts: Vec<usize> = tail.char_indices().map(|(i, _)| i).collect();
+    let suffix = |index: usize| tail[starts.get(index).copied().unwrap_or(tail.len())..].trim_start();
+    let (mut low, mut high) = (0, starts.len());
+    while low < high {
+        let mid = (low + high) / 2;
+        if count_tokens(&join(suffix(mid))) <= max_tokens {
+            high = mid;
+        } else {
+            low = mid + 1;
+        }
+    }
+
+    tracing::warn!(
+        "Whisper prompt is too long, dropped the first {} of {} characters",
+        low,
+        starts.len()
+    );
+    join(suffix(low))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prompt(initial_prompt: &str, vocabulary: &[&str]) -> WhisperPrompt {
+        WhisperPrompt {
+            initial_prompt: initial_prompt.to_string(),
+            vocabulary: vocabulary.iter().map(|word| word.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_build_prompt() {
+        assert_eq!(build_prompt(&prompt("", &[])), "");
+        assert_eq!(build_prompt(&prompt("会議の記録。", &[])), "会議の記録。");
+        assert_eq!(build_prompt(&prompt("", &["Tauri", "Svelte"])), "Tauri, Svelte");
+        assert_eq!(
+            build_prompt(&prompt("会議の記録。", &["Tauri", "Svelte"])),
+            "会議の記録。 Tauri, Svelte"
+        );
+    }
+
+    #[test]
+    fn test_chunk_prompt_puts_previous_text_last() {
+        let count_chars = |text: &str| text.chars().count();
+
+        assert_eq!(chunk_prompt(&prompt("", &[]), "", 224, count_chars), "");
+        assert_eq!(
+            chunk_prompt(&prompt("会議の記録。", &["Tauri"]), " 前の文です。", 224, count_chars),
+            "会議の記録。 Tauri 前の文です。"
+        );
+    }
+
+    #[test]
+    fn test_chunk_prompt_shortens_previous_text_first() {
+        let count_chars = |text: &str| text.chars().count();
+
+        let meeting = prompt("会議の記録。", &["Tauri"]);
+        assert_eq!(
+            chunk_prompt(&meeting, "一文目。二文目。三文目。", 21, count_chars),
+            "会議の記録。 Tauri 二文目。三文目。"
+        );
+
+        // A prompt that is too long on its own loses its start as before
+        assert_eq!(chunk_prompt(&meeting, "前の文。", 5, count_chars), "Tauri");
+    }
+
+    #[test]
+    fn test_fit_prompt_keeps_the_end() {
+        let count_chars = |text: &str| text.chars().count();
+
+        assert_eq!(fit_prompt("短い", 10, count_chars), "短い");
+        assert_eq!(fit_prompt("議事録です。 Tauri, Svelte", 13, count_chars), "Tauri, Svelte");
+        assert_eq!(fit_prompt("abc", 0, count_chars), "");
+    }
+}
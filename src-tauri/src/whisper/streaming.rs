@@ -0,0 +1,382 @@
+use serde::Serialize;
+
+use super::filter::{filter_transcription, FilterRemoval};
+use super::transcribe::{TranscriptSegment, TranscriptionResult};
+use crate::config::OutputFilterSettings;
+
+/// Sample rate of the audio passed to Whisper
+const SAMPLE_RATE: usize = 16000;
+/// Whisper reads 30 seconds at once. A window that has grown to this length is
+/// committed as it is, even without a confirmed segment to cut at.
+pub const MAX_WINDOW_SECS: u32 = 28;
+
+/// Text of a live transcription, emitted as `transcription-partial`
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PartialTranscript {
+    /// Text confirmed by consecutive hypotheses; only ever grows
+    pub stable: String,
+    /// Latest hypothesis for the rest of the audio; may still change
+    pub unstable: String,
+}
+
+/// Split text into the units hypotheses are compared by: words of Latin script,
+/// single characters otherwise (Japanese has no spaces). Units keep their
+/// leading whitespace, so concatenating them restores the text.
+pub(super) fn split_units(text: &str) -> Vec<String> {
+    let is_word_char = |c: char| c.is_ascii_alphanumeric() || c == '\'';
+    let mut units = Vec::new();
+    let mut current = String::new();
+
+    for c in text.chars() {
+        let last = current.chars().last();
+        if c.is_whitespace() {
+            if !current.trim().is_empty() {
+                units.push(std::mem::take(&mut current));
+            }
+            current.push(c);
+        } else if is_word_char(c) {
+            if last.is_some_and(|l| !l.is_whitespace() && !is_word_char(l)) {
+                units.push(std::mem::take(&mut current));
+            }
+            current.push(c);
+        } else {
+            if last.is_some_and(|l| !l.is_whitespace()) {
+                units.push(std::mem::take(&mut current));
+            }
+            current.push(c);
+            units.push(std::mem::take(&mut current));
+        }
+    }
+    if !current.trim().is_empty() {
+        units.push(current);
+    }
+    units
+}
+
+fn segment_units(segments: &[TranscriptSegment]) -> Vec<Vec<String>> {
+    segments.iter().map(|segment| split_units(&segment.text)).collect()
+}
+
+/// Number of leading units two sequences agree on (ignoring whitespace)
+fn common_prefix_len(a: &[String], b: &[String]) -> usize {
+    a.iter().zip(b).take_while(|(a, b)| a.trim() == b.trim()).count()
+}
+
+/// Reconciles hypotheses of overlapping audio windows (LocalAgreement-2):
+/// text is committed once two consecutive hypotheses agree on it.
+#[derive(Debug, Default)]
+pub struct LocalAgreement {
+    committed: String,
+    /// Committed units that belong to the current audio window
+    window_units: Vec<String>,
+    /// Uncommitted units of the last hypothesis
+    pending: Vec<String>,
+}
+
+impl LocalAgreement {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Units of a hypothesis that follow the text committed in the window
+    fn new_units(&self, units: &[String]) -> Vec<String> {
+        units[self.window_units.len().min(units.len())..].to_vec()
+    }
+
+    /// Feed the units of a hypothesis for the whole current window
+    pub fn update(&mut self, units: &[String]) -> PartialTranscript {
+        let rest = self.new_units(units);
+        let agreed = common_prefix_len(&self.pending, &rest);
+        for unit in &rest[..agreed] {
+            self.committed.push_str(unit);
+        }
+        self.window_units.extend_from_slice(&rest[..agreed]);
+        self.pending = rest[agreed..].to_vec();
+        self.partial()
+    }
+
+    pub fn partial(&self) -> PartialTranscript {
+        let stable = self.committed.trim_start().to_string();
+        let unstable = self.pending.concat();
+        let unstable = if stable.is_empty() { unstable.trim_start().to_string() } else { unstable };
+        PartialTranscript { stable, unstable }
+    }
+
+    /// The window moved past its first `count` committed units
+    pub fn advance(&mut self, count: usize) {
+        self.window_units.drain(..count.min(self.window_units.len()));
+    }
+
+    /// Commit the last hypothesis as it is; the window starts over afterwards
+    pub fn commit_pending(&mut self) {
+        for unit in self.pending.drain(..) {
+            self.committed.push_str(&unit);
+        }
+        self.window_units.clear();
+    }
+
+    /// Commit the final hypothesis of the window and return the whole text
+    pub fn finish(mut self, units: &[String]) -> String {
+        self.pending = self.new_units(units);
+        self.commit_pending();
+        self.committed.trim().to_string()
+    }
+}
+
+/// Sliding window over a growing recording for live transcription.
+///
+/// The window is transcribed repeatedly as audio arrives. Once it is longer than
+/// `window_secs`, it is moved past the segments whose text has been committed.
+/// Every hypothesis is filtered before it is reconciled, so the agreed text and
+/// the kept segments never contain filtered text.
+pub struct LiveTranscript {
+    agreement: LocalAgreement,
+    /// 16kHz audio from the window start to the end of the recording so far
+    window: Vec<f32>,
+    /// Position of the window in the recording (16kHz samples)
+    window_start: usize,
+    window_samples: usize,
+    /// Committed segments before the window, in recording time
+    segments: Vec<TranscriptSegment>,
+    filter: OutputFilterSettings,
+    /// Text filtered from the committed segments, in recording time
+    removals: Vec<FilterRemoval>,
+}
+
+impl LiveTranscript {
+    pub fn new(window_secs: u32, filter: OutputFilterSettings) -> Self {
+        Self {
+            agreement: LocalAgreement::new(),
+            window: Vec::new(),
+            window_start: 0,
+            window_samples: window_secs.min(MAX_WINDOW_SECS) as usize * SAMPLE_RATE,
+            segments: Vec::new(),
+            filter,
+            removals: Vec::new(),
+        }
+    }
+
+    /// Append 16kHz audio recorded since the last call
+    pub fn push_audio(&mut self, samples: &[f32]) {
+        self.window.extend_from_slice(samples);
+    }
+
+    /// Audio to transcribe next
+    pub fn window(&self) -> &[f32] {
+        &self.window
+    }
+
+    /// Position of the window in the recording (16kHz samples)
+    pub fn window_start(&self) -> usize {
+        self.window_start
+    }
+
+    /// Length of the recording received so far (16kHz samples)
+    pub fn recorded_samples(&self) -> usize {
+        self.window_start + self.window.len()
+    }
+
+    /// Reconcile a hypothesis for the current window and slide the window when it is full
+    pub fn update(&mut self, hypothesis: &TranscriptionResult) -> PartialTranscript {
+        let mut hypothesis = hypothesis.clone();
+        let removals = filter_transcription(&mut hypothesis, &self.filter, false);
+        let units = segment_units(&hypothesis.segments);
+        self.agreement.update(&units.concat());
+
+        if self.window.len() > MAX_WINDOW_SECS as usize * SAMPLE_RATE {
+            // No confirmed boundary in time, keep the hypothesis as it is
+            tracing::debug!("Live transcription window is full, committing the hypothesis");
+            self.agreement.commit_pending();
+            self.keep_segments(&hypothesis.segments);
+            self.keep_removals(&removals, f32::INFINITY);
+            self.window_start += self.window.len();
+            self.window.clear();
+        } else if self.window.len() > self.window_samples {
+            self.slide(&hypothesis, &units, &removals);
+        }
+        self.agreement.partial()
+    }
+
+    /// Move the window past the leading segments whose text is fully committed
+    fn slide(&mut self, hypothesis: &TranscriptionResult, units: &[Vec<String>], removals: &[FilterRemoval]) {
+        let committed = self.agreement.window_units.len();
+        let mut count = 0;
+        let mut end_units = 0;
+        for segment in units {
+            if end_units + segment.len() > committed {
+                break;
+            }
+            end_units += segment.len();
+            count += 1;
+        }
+        if count == 0 {
+            return;
+        }
+
+        let end_secs = hypothesis.segments[count - 1].end_secs;
+        let cut = ((end_secs * SAMPLE_RATE as f32) as usize).min(self.window.len());
+        self.keep_segments(&hypothesis.segments[..count]);
+        self.keep_removals(removals, end_secs);
+        self.agreement.advance(end_units);
+        self.window.drain(..cut);
+        self.window_start += cut;
+    }
+
+    /// Keep segments of the window (window time) in recording time
+    fn keep_segments(&mut self, segments: &[TranscriptSegment]) {
+        let offset = self.window_start as f32 / SAMPLE_RATE as f32;
+        self.segments.extend(segments.iter().cloned().map(|mut segment| {
+            segment.start_secs += offset;
+            segment.end_secs += offset;
+            segment
+        }));
+    }
+
+    /// Keep what the filter removed before `end_secs` (window time) in recording time
+    fn keep_removals(&mut self, removals: &[FilterRemoval], end_secs: f32) {
+        let offset = self.window_start as f32 / SAMPLE_RATE as f32;
+        self.removals.extend(
+            removals
+                .iter()
+                .filter(|removal| removal.start_secs < end_secs)
+                .cloned()
+                .map(|mut removal| {
+                    removal.start_secs += offset;
+                    removal
+                }),
+        );
+    }
+
+    /// Combine the committed text with the transcription of the final window
+    /// (audio from `window_start` to the end of the recording), filtered like the
+    /// other hypotheses. Returns the result and everything the filter removed from it.
+    pub fn finish(mut self, mut last_window: TranscriptionResult) -> (TranscriptionResult, Vec<FilterRemoval>) {
+        let removals = filter_transcription(&mut last_window, &self.filter, false);
+        self.keep_removals(&removals, f32::INFINITY);
+        let units = segment_units(&last_window.segments);
+        self.keep_segments(&last_window.segments);
+        last_window.text = self.agreement.finish(&units.concat());
+        last_window.segments = self.segments;
+        (last_window, self.removals)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::whisper::filter::FilterReason;
+
+    fn units(text: &str) -> Vec<String> {
+        split_units(text)
+    }
+
+    fn hypothesis(segments: &[(f32, f32, &str)]) -> TranscriptionResult {
+        TranscriptionResult {
+            text: String::new(),
+            segments: segments
+                .iter()
+                .map(|&(start_secs, end_secs, text)| TranscriptSegment {
+                    start_secs,
+                    end_secs,
+                    text: text.to_string(),
+                    avg_token_prob: 0.9,
+                    no_speech_prob: 0.0,
+                })
+                .collect(),
+            language: None,
+        }
+    }
+
+    #[test]
+    fn test_split_units() {
+        assert_eq!(units(" Hello, world"), vec![" Hello", ",", " world"]);
+        assert_eq!(units("今日は"), vec!["今", "日", "は"]);
+        assert_eq!(units(" it's 2024 "), vec![" it's", " 2024"]);
+        assert_eq!(units("").len(), 0);
+    }
+
+    #[test]
+    fn test_local_agreement_commits_agreeing_prefix() {
+        let mut agreement = LocalAgreement::new();
+
+        let partial = agreement.update(&units(" The quick"));
+        assert_eq!(partial.stable, "");
+        assert_eq!(partial.unstable, "The quick");
+
+        let partial = agreement.update(&units(" The quick brown fax"));
+        assert_eq!(partial.stable, "The quick");
+        assert_eq!(partial.unstable, " brown fax");
+
+        // Committed text is never revised, the rest follows the latest hypothesis
+        let partial = agreement.update(&units(" The quick brown fox jumps"));
+        assert_eq!(partial.stable, "The quick brown");
+        assert_eq!(partial.unstable, " fox jumps");
+
+        assert_eq!(agreement.finish(&units(" The quick brown fox jumps over")), "The quick brown fox jumps over");
+    }
+
+    #[test]
+    fn test_live_transcript_slides_past_committed_segments() {
+        let mut live = LiveTranscript::new(5, OutputFilterSettings::default());
+        live.push_audio(&vec![0.0; 6 * SAMPLE_RATE]);
+
+        let first = hypothesis(&[(0.0, 2.0, "おはよう。"), (2.0, 6.0, "今日は")]);
+        live.update(&first);
+        assert_eq!(live.window_start(), 0);
+
+        // The first segment is confirmed, so the window starts after it
+        let second = hypothesis(&[(0.0, 2.0, "おはよう。"), (2.0, 6.0, "今日は晴れ")]);
+        let partial = live.update(&second);
+        assert_eq!(partial.stable, "おはよう。今日は");
+        assert_eq!(partial.unstable, "晴れ");
+        assert_eq!(live.window_start(), 2 * SAMPLE_RATE);
+        assert_eq!(live.recorded_samples(), 6 * SAMPLE_RATE);
+
+        // Hypotheses of the new window still contain the committed "今日は"
+        live.push_audio(&vec![0.0; SAMPLE_RATE]);
+        let (result, _) = live.finish(hypothesis(&[(0.0, 5.0, "今日は晴れです。")]));
+        assert_eq!(result.text, "おはよう。今日は晴れです。");
+        let times: Vec<_> = result.segments.iter().map(|s| (s.start_secs, s.end_secs)).collect();
+        assert_eq!(times, vec![(0.0, 2.0), (2.0, 7.0)]);
+    }
+
+    #[test]
+    fn test_live_transcript_commits_full_window() {
+        let mut live = LiveTranscript::new(20, OutputFilterSettings::default());
+        live.push_audio(&vec![0.0; (MAX_WINDOW_SECS as usize + 1) * SAMPLE_RATE]);
+
+        let partial = live.update(&hypothesis(&[(0.0, 29.0, "長い話")]));
+        assert_eq!(partial.stable, "長い話");
+        assert_eq!(live.window().len(), 0);
+
+        let (result, _) = live.finish(hypothesis(&[]));
+        assert_eq!(result.text, "長い話");
+    }
+
+    #[test]
+    fn test_live_transcript_filters_hypotheses() {
+        let mut live = LiveTranscript::new(5, OutputFilterSettings::default());
+        live.push_audio(&vec![0.0; 6 * SAMPLE_RATE]);
+
+        // Repetition and a blocklisted phrase never reach the agreed text
+        let looping = "はい、わかりました。わかりました。わかりました。わかりました。わかりました。";
+        let first = hypothesis(&[(0.0, 2.0, looping), (2.0, 6.0, "ご視聴ありがとうございました。")]);
+        live.update(&first);
+        let partial = live.update(&first);
+        assert_eq!(partial.stable, "はい、わかりました。わかりました。わかりました。");
+        assert_eq!(live.window_start(), 2 * SAMPLE_RATE);
+
+        live.push_audio(&vec![0.0; SAMPLE_RATE]);
+        let (result, removals) = live.finish(hypothesis(&[(0.0, 5.0, "ご視聴ありがとうございました。")]));
+        assert_eq!(result.text, "はい、わかりました。わかりました。わかりました。");
+        let segment_text: String = result.segments.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(result.text, segment_text);
+
+        // Each removal is reported once, in recording time
+        let removed: Vec<_> = removals.iter().map(|r| (r.reason, r.start_secs)).collect();
+        assert_eq!(
+            removed,
+            vec![(FilterReason::Repetition, 0.0), (FilterReason::Blocklist, 2.0)]
+        );
+    }
+}
@@ -1,65 +1,338 @@
+use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters};
 
+use super::backend::WhisperBackend;
+use super::chunker::{append_chunk, plan_chunks};
+use super::prompt::{build_prompt, chunk_prompt, fit_prompt, MAX_PROMPT_TOKENS};
+use crate::config::{DecodingSettings, WhisperPrompt, WhisperSettings, AUTO_LANGUAGE};
+
+/// A segment of the transcription with its position in the audio
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptSegment {
+    /// Start time in seconds
+    pub start_secs: f32,
+    /// End time in seconds
+    pub end_secs: f32,
+    pub text: String,
+    /// Average probability of the text tokens (0.0-1.0)
+    pub avg_token_prob: f32,
+    /// Probability that the segment contains no speech (0.0-1.0)
+    pub no_speech_prob: f32,
+}
+
+/// Result of a transcription
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptionResult {
+    /// Text of all segments
+    pub text: String,
+    pub segments: Vec<TranscriptSegment>,
+    /// Spoken language code (e.g. "ja"). Detected by Whisper when the language setting is "auto".
+    pub language: Option<String>,
+}
+
+impl TranscriptionResult {
+    /// Result without segments (e.g. placeholder text)
+    pub fn from_text(text: String) -> Self {
+        Self {
+            text,
+            segments: Vec::new(),
+            language: None,
+        }
+    }
+
+    /// Convert segment times, e.g. from the VAD-joined audio to the original recording
+    pub fn map_times(&mut self, map: impl Fn(f32) -> f32) {
+        for segment in &mut self.segments {
+            segment.start_secs = map(segment.start_secs);
+            segment.end_secs = map(segment.end_secs);
+        }
+    }
+}
+
+/// Join segment texts, one line per segment when `insert_newline` is set
+pub(super) fn join_segment_texts(segments: &[TranscriptSegment], insert_newline: bool) -> String {
+    let separator = if insert_newline { "\n" } else { "" };
+    segments
+        .iter()
+        .map(|segment| segment.text.as_str())
+        .collect::<Vec<_>>()
+        .join(separator)
+        .trim()
+        .to_string()
+}
+
+/// Language passed to Whisper: the configured code, or "auto" when it is unknown to Whisper
+fn whisper_language(language: &str) -> &str {
+    if language == AUTO_LANGUAGE || whisper_rs::get_lang_id(language).is_some() {
+        language
+    } else {
+        tracing::warn!("Unknown transcription language {:?}, detecting automatically", language);
+        AUTO_LANGUAGE
+    }
+}
+
+/// Beam search when a beam width is set, greedy decoding otherwise
+fn sampling_strategy(decoding: &DecodingSettings) -> SamplingStrategy {
+    if decoding.beam_size > 1 {
+        SamplingStrategy::BeamSearch {
+            beam_size: decoding.beam_size as i32,
+            // -1.0 = whisper.cpp default patience
+            patience: -1.0,
+        }
+    } else {
+        SamplingStrategy::Greedy {
+            best_of: decoding.best_of.max(1) as i32,
+        }
+    }
+}
+
 pub struct WhisperTranscriber {
     ctx: WhisperContext,
+    backend: WhisperBackend,
 }
 
 impl WhisperTranscriber {
-    pub fn new(model_path: PathBuf) -> Result<Self, Box<dyn std::error::Error>> {
+    pub fn new(
+        model_path: PathBuf,
+        settings: &WhisperSettings,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
         tracing::info!("Loading Whisper model from: {:?}", model_path);
 
+        let backend = WhisperBackend::resolve(WhisperBackend::compiled(), settings.use_gpu);
         let mut params = WhisperContextParameters::default();
-        // Explicitly enable GPU (CUDA) if available
-        params.use_gpu(true);
-        params.gpu_device(0);
+        params.use_gpu(backend.is_gpu());
+        params.gpu_device(i32::try_from(settings.gpu_device).unwrap_or(0));
 
-        tracing::info!("GPU acceleration enabled: use_gpu=true, device=0");
+        tracing::info!(
+            "Whisper backend: {:?} (use_gpu={}, device={})",
+            backend,
+            settings.use_gpu,
+            settings.gpu_device
+        );
+        tracing::debug!("whisper.cpp system info: {}", whisper_rs::print_system_info());
 
         let ctx = WhisperContext::new_with_params(&model_path.to_string_lossy(), params)?;
 
-        tracing::info!("Whisper model loaded successfully (CUDA enabled)");
+        tracing::info!("Whisper model loaded successfully ({:?})", backend);
 
-        Ok(Self { ctx })
+        Ok(Self { ctx, backend })
     }
 
+    /// Backend the model was loaded on
+    pub fn backend(&self) -> WhisperBackend {
+        self.backend
+    }
+
+    /// Number of tokens the model's tokenizer splits `text` into
+    fn count_tokens(&self, text: &str) -> usize {
+        // A token covers at least one byte, so `len + 1` never makes tokenize fail
+        self.ctx
+            .tokenize(text, text.len() + 1)
+            .map(|tokens| tokens.len())
+            .unwrap_or(usize::MAX)
+    }
+
+    /// Transcribe audio of any length. Audio longer than one Whisper window is split
+    /// at pauses (`boundaries`, e.g. between VAD segments) and transcribed chunk by
+    /// chunk, each with the text of the previous chunk as the prompt.
+    pub fn transcribe_long(
+        &self,
+        audio_data: &[f32],
+        settings: &WhisperSettings,
+        prompt: &WhisperPrompt,
+        boundaries: &[usize],
+        abort: Option<&Arc<AtomicBool>>,
+        mut on_progress: impl FnMut(f32),
+    ) -> Result<TranscriptionResult, Box<dyn std::error::Error>> {
+        let chunks = plan_chunks(audio_data, boundaries);
+        if chunks.len() == 1 {
+            return self.transcribe_with_options(audio_data, settings, prompt, abort);
+        }
+
+        tracing::info!("Transcribing {} samples in {} chunks", audio_data.len(), chunks.len());
+        let mut result = TranscriptionResult::from_text(String::new());
+        let mut previous_text = String::new();
+        for (i, chunk) in chunks.iter().enumerate() {
+            if abort.is_some_and(|abort| abort.load(Ordering::Acquire)) {
+                return Err("Transcription aborted".into());
+            }
+            on_progress(i as f32 / chunks.len() as f32);
+            let chunk_prompt = chunk_prompt(prompt, &previous_text, MAX_PROMPT_TOKENS, |text| {
+                self.count_tokens(text)
+            });
+            let transcription =
+                self.transcribe_with_prompt(&audio_data[chunk.clone()], settings, &chunk_prompt, abort)?;
+            previous_text = join_segment_texts(&transcription.segments, false);
+            append_chunk(&mut result, transcription, chunk.start as f32 / 16000.0);
+        }
+        result.text = join_segment_texts(&result.segments, settings.insert_newline);
+        Ok(result)
+    }
+
+    /// Transcribe audio within one Whisper window. Setting `abort` stops Whisper
+    /// mid-decode, and the transcription fails.
     pub fn transcribe_with_options(
         &self,
         audio_data: &[f32],
-        insert_newline: bool,
-    ) -> Result<String, Box<dyn std::error::Error>> {
-        let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
+        settings: &WhisperSettings,
+        prompt: &WhisperPrompt,
+        abort: Option<&Arc<AtomicBool>>,
+    ) -> Result<TranscriptionResult, Box<dyn std::error::Error>> {
+        let initial_prompt = fit_prompt(&build_prompt(prompt), MAX_PROMPT_TOKENS, |text| self.count_tokens(text));
+        self.transcribe_with_prompt(audio_data, settings, &initial_prompt, abort)
+    }
 
-        // Japanese language setting
-        params.set_language(Some("ja"));
-        params.set_translate(false);
+    /// Transcribe audio within one Whisper window, with a prompt already fitted to
+    /// `MAX_PROMPT_TOKENS`
+    fn transcribe_with_prompt(
+        &self,
+        audio_data: &[f32],
+        settings: &WhisperSettings,
+        initial_prompt: &str,
+        abort: Option<&Arc<AtomicBool>>,
+    ) -> Result<TranscriptionResult, Box<dyn std::error::Error>> {
+        let decoding = &settings.decoding;
+        let mut params = FullParams::new(sampling_strategy(decoding));
+        params.set_temperature(decoding.temperature);
+        params.set_temperature_inc(decoding.temperature_inc);
+        params.set_no_speech_thold(decoding.no_speech_thold);
+        params.set_logprob_thold(decoding.logprob_thold);
+        params.set_suppress_blank(decoding.suppress_blank);
+        params.set_suppress_nst(decoding.suppress_non_speech);
+        if decoding.n_threads > 0 {
+            params.set_n_threads(decoding.n_threads as i32);
+        }
+        tracing::info!("Decoding parameters: {:?}", decoding);
+
+        // Fixed language or "auto" (detect), optionally translated to English
+        let language = whisper_language(&settings.language);
+        params.set_language(Some(language));
+        params.set_translate(settings.translate);
         params.set_print_special(false);
         params.set_print_progress(false);
         params.set_print_realtime(false);
         params.set_print_timestamps(false);
+        if let Some(abort) = abort {
+            let abort = abort.clone();
+            params.set_abort_callback_safe(move || abort.load(Ordering::Acquire));
+        }
+
+        // Bias the decoder towards the user's vocabulary
+        if !initial_prompt.is_empty() {
+            tracing::info!("Initial prompt: {}", initial_prompt);
+            params.set_initial_prompt(initial_prompt);
+        }
 
-        tracing::info!("Starting transcription for {} samples", audio_data.len());
+        tracing::info!(
+            "Starting transcription for {} samples (language: {}, translate: {})",
+            audio_data.len(),
+            language,
+            settings.translate
+        );
 
         let mut state = self.ctx.create_state()?;
         state.full(params, audio_data)?;
+        if abort.is_some_and(|abort| abort.load(Ordering::Acquire)) {
+            return Err("Transcription aborted".into());
+        }
 
         let num_segments = state.full_n_segments();
         tracing::info!("Transcription complete. Segments: {}", num_segments);
 
-        let separator = if insert_newline { "\n" } else { "" };
-        let mut result = String::new();
+        let language = if language == AUTO_LANGUAGE {
+            let detected = whisper_rs::get_lang_str(state.full_lang_id_from_state());
+            tracing::info!("Detected language: {:?}", detected);
+            detected.map(|code| code.to_string())
+        } else {
+            Some(language.to_string())
+        };
+
+        // Token IDs from end-of-text on are special and timestamp tokens
+        let first_special_token = self.ctx.token_eot();
+        let mut segments = Vec::new();
         for i in 0..num_segments {
-            if let Some(segment) = state.get_segment(i) {
-                // Use to_str_lossy to handle any encoding issues gracefully
-                if let Ok(text) = segment.to_str_lossy() {
-                    if !result.is_empty() {
-                        result.push_str(separator);
-                    }
-                    result.push_str(&text);
-                }
-            }
+            let Some(segment) = state.get_segment(i) else {
+                continue;
+            };
+            // Use to_str_lossy to handle any encoding issues gracefully
+            let Ok(text) = segment.to_str_lossy() else {
+                continue;
+            };
+
+            let probabilities: Vec<f32> = (0..segment.n_tokens())
+                .filter_map(|t| segment.get_token(t))
+                .filter(|token| token.token_id() < first_special_token)
+                .map(|token| token.token_probability())
+                .collect();
+            let avg_token_prob = if probabilities.is_empty() {
+                0.0
+            } else {
+                probabilities.iter().sum::<f32>() / probabilities.len() as f32
+            };
+
+            // Whisper timestamps are in centiseconds
+            segments.push(TranscriptSegment {
+                start_secs: segment.start_timestamp() as f32 / 100.0,
+                end_secs: segment.end_timestamp() as f32 / 100.0,
+                text: text.into_owned(),
+                avg_token_prob,
+                no_speech_prob: segment.no_speech_probability(),
+            });
+        }
+
+        Ok(TranscriptionResult {
+            text: join_segment_texts(&segments, settings.insert_newline),
+            segments,
+            language,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(start_secs: f32, end_secs: f32, text: &str) -> TranscriptSegment {
+        TranscriptSegment {
+            start_secs,
+            end_secs,
+            text: text.to_string(),
+            avg_token_prob: 0.9,
+            no_speech_prob: 0.01,
         }
+    }
+
+    #[test]
+    fn test_join_segment_texts() {
+        let segments = [segment(0.0, 1.5, " こんにちは。"), segment(1.5, 3.0, "今日は晴れです。 ")];
+        assert_eq!(join_segment_texts(&segments, false), "こんにちは。今日は晴れです。");
+        assert_eq!(join_segment_texts(&segments, true), "こんにちは。\n今日は晴れです。");
+        assert_eq!(join_segment_texts(&[], true), "");
+    }
+
+    #[test]
+    fn test_map_times() {
+        let mut result = TranscriptionResult {
+            text: String::new(),
+            segments: vec![segment(0.5, 2.0, "a")],
+            language: Some("ja".to_string()),
+        };
+        result.map_times(|secs| secs + 10.0);
+        assert_eq!((result.segments[0].start_secs, result.segments[0].end_secs), (10.5, 12.0));
+    }
+
+    #[test]
+    fn test_sampling_strategy() {
+        let greedy = sampling_strategy(&DecodingSettings::default());
+        assert!(matches!(greedy, SamplingStrategy::Greedy { best_of: 1 }));
 
-        Ok(result.trim().to_string())
+        let beam = sampling_strategy(&DecodingSettings {
+            beam_size: 5,
+            ..DecodingSettings::default()
+        });
+        assert!(matches!(beam, SamplingStrategy::BeamSearch { beam_size: 5, .. }));
     }
 }
@@ -8,8 +8,31 @@ import type {
   LlmProvider,
   OutputMode,
   LogEntry,
+  ShortcutTarget,
+  InputDeviceInfo,
+  ChannelMix,
+  ArchiveSource,
+  ResampleQuality,
+  VadEngine,
+  WhisperPrompt,
+  DecodingSettings,
+  VadSettings,
+  HandsFreeSettings,
+  FileTranscriptionProgress,
+  AudioLevel,
+  LevelWarning,
+  TranscriptSegment,
+  WhisperBackendInfo,
+  LiveTranscriptionSettings,
+  PartialTranscript,
+  OutputFilterSettings,
+} from "$lib/types";
+import {
+  defaultDecodingSettings,
+  defaultLiveTranscriptionSettings,
+  llmProviderDefaultUrls,
+  TRANSCRIPTION_CANCELLED,
 } from "$lib/types";
-import { llmProviderDefaultUrls } from "$lib/types";
 
 class SettingsStore {
   // UI state
@@ -20,9 +43,51 @@ class SettingsStore {
   selectedModel = $state("large-v3-turbo");
   insertNewline = $state(true);
   maxRecordingSeconds = $state(300);
+  autoStopSilenceMs = $state(0);
+  transcriptionLanguage = $state("ja");
+  translateToEnglish = $state(false);
+  whisperPrompt = $state<WhisperPrompt>({ initial_prompt: "", vocabulary: [] });
+  presetPrompts = $state<Partial<Record<PromptPreset, WhisperPrompt>>>({});
+  decoding = $state<DecodingSettings>({ ...defaultDecodingSettings });
+  useGpu = $state(true);
+  gpuDevice = $state(0);
+  whisperBackend = $state<WhisperBackendInfo | null>(null);
+  loadedGpuSettings = $state<string | null>(null);
+  liveTranscription = $state<LiveTranscriptionSettings>({ ...defaultLiveTranscriptionSettings });
+  // Loaded from the backend, which owns the default blocklist
+  outputFilter = $state<OutputFilterSettings>({
+    enabled: true,
+    blocklist: [],
+    max_repeats: 3,
+    no_speech_prob: 0.8,
+  });
   currentLoadedModel = $state<string | null>(null);
   isModelInitialized = $state(false);
 
+  // Audio input settings ("" = OS default device)
+  inputDevices = $state<InputDeviceInfo[]>([]);
+  selectedInputDevice = $state("");
+  reconnectOnDeviceLost = $state(true);
+  alwaysWarm = $state(false);
+  prerollMs = $state(500);
+  channelMix = $state<ChannelMix>({ type: "Average" });
+  archiveRecordings = $state(false);
+  archiveSource = $state<ArchiveSource>("Resampled");
+  resampleQuality = $state<ResampleQuality>("Best");
+
+  // VAD settings
+  vadEnabled = $state(true);
+  vadEngine = $state<VadEngine>("Silero");
+  vadThreshold = $state(0.5);
+  vadPaddingChunks = $state(3);
+  vadKeepOriginalRatio = $state(0.8);
+  vadSegmentGapMs = $state(300);
+
+  // Hands-free mode settings
+  handsFreeEnabled = $state(false);
+  silenceTimeoutMs = $state(800);
+  minSpeechMs = $state(250);
+
   // LLM settings
   llmEnabled = $state(false);
   llmProvider = $state<LlmProvider>("Ollama");
@@ -43,7 +108,9 @@ class SettingsStore {
 
   // Shortcut settings
   shortcutKey = $state("Ctrl+Space");
+  cancelShortcutKey = $state("");
   isEditingShortcut = $state(false);
+  shortcutEditTarget = $state<ShortcutTarget>("recording_toggle");
   shortcutError = $state("");
   pendingShortcut = $state("");
   shortcutChanged = $state(false);
@@ -54,12 +121,23 @@ class SettingsStore {
 
   // Status
   isRecording = $state(false);
+  // Hands-free mode is listening / an utterance is in progress
+  isListening = $state(false);
+  isSpeaking = $state(false);
+  audioLevel = $state<AudioLevel | null>(null);
+  levelWarning = $state<LevelWarning | null>(null);
   isTranscribing = $state(false);
   isDownloading = $state(false);
   downloadProgress = $state<DownloadProgress | null>(null);
   transcriptionResult = $state("");
+  transcriptionSegments = $state<TranscriptSegment[]>([]);
+  partialTranscript = $state<PartialTranscript | null>(null);
+  // Transcription jobs queued or running
+  pendingJobs = $state(0);
   statusMessage = $state("モデルを選択して初期化してください");
   errorMessage = $state("");
+  fileTranscription = $state<FileTranscriptionProgress | null>(null);
+  supportedAudioExtensions = $state<string[]>([]);
 
   // Log viewer
   showLogViewer = $state(false);
@@ -70,7 +148,12 @@ class SettingsStore {
 
   // Derived state
   isProcessing = $derived(
-    this.isRecording || this.isTranscribing || this.isDownloading || this.isLlmRefining
+    this.isRecording ||
+      this.isTranscribing ||
+      this.isDownloading ||
+      this.isLlmRefining ||
+      this.pendingJobs > 0 ||
+      this.fileTranscription !== null
   );
 
   // Methods
@@ -108,6 +191,35 @@ class SettingsStore {
       this.selectedModel = settings.whisper.model_name;
       this.insertNewline = settings.whisper.insert_newline ?? true;
       this.maxRecordingSeconds = settings.whisper.max_recording_seconds ?? 300;
+      this.autoStopSilenceMs = settings.whisper.auto_stop_silence_ms ?? 0;
+      this.transcriptionLanguage = settings.whisper.language ?? "ja";
+      this.translateToEnglish = settings.whisper.translate ?? false;
+      this.whisperPrompt = settings.whisper.prompt ?? { initial_prompt: "", vocabulary: [] };
+      this.presetPrompts = settings.whisper.preset_prompts ?? {};
+      this.decoding = { ...defaultDecodingSettings, ...settings.whisper.decoding };
+      this.useGpu = settings.whisper.use_gpu ?? true;
+      this.gpuDevice = settings.whisper.gpu_device ?? 0;
+      this.liveTranscription = { ...defaultLiveTranscriptionSettings, ...settings.whisper.live };
+      if (settings.whisper.filter) {
+        this.outputFilter = settings.whisper.filter;
+      }
+      this.selectedInputDevice = settings.audio?.input_device ?? "";
+      this.reconnectOnDeviceLost = settings.audio?.reconnect_on_device_lost ?? true;
+      this.alwaysWarm = settings.audio?.always_warm ?? false;
+      this.prerollMs = settings.audio?.preroll_ms ?? 500;
+      this.channelMix = settings.audio?.channel_mix ?? { type: "Average" };
+      this.archiveRecordings = settings.audio?.archive_recordings ?? false;
+      this.archiveSource = settings.audio?.archive_source ?? "Resampled";
+      this.resampleQuality = settings.audio?.resample_quality ?? "Best";
+      this.vadEnabled = settings.vad?.enabled ?? true;
+      this.vadEngine = settings.vad?.engine ?? "Silero";
+      this.vadThreshold = settings.vad?.threshold ?? 0.5;
+      this.vadPaddingChunks = settings.vad?.padding_chunks ?? 3;
+      this.vadKeepOriginalRatio = settings.vad?.keep_original_ratio ?? 0.8;
+      this.vadSegmentGapMs = settings.vad?.segment_gap_ms ?? 300;
+      this.handsFreeEnabled = settings.hands_free?.enabled ?? false;
+      this.silenceTimeoutMs = settings.hands_free?.silence_timeout_ms ?? 800;
+      this.minSpeechMs = settings.hands_free?.min_speech_ms ?? 250;
       this.llmEnabled = settings.llm.enabled;
       this.llmProvider = settings.llm.provider || "Ollama";
       this.llmApiUrl = settings.llm.api_url || llmProviderDefaultUrls[this.llmProvider];
@@ -116,6 +228,7 @@ class SettingsStore {
       this.customPrompt = settings.llm.custom_prompt || "";
       this.outputMode = settings.output_mode || "Both";
       this.shortcutKey = settings.shortcut?.recording_toggle || "Ctrl+Space";
+      this.cancelShortcutKey = settings.shortcut?.cancel ?? "";
       console.log(
         "Loaded settings, model:",
         this.selectedModel,
@@ -165,6 +278,199 @@ class SettingsStore {
     }
   }
 
+  async saveAutoStopSilence() {
+    try {
+      await invoke("save_auto_stop_silence", { silenceMs: this.autoStopSilenceMs });
+      console.log("Saved auto-stop silence:", this.autoStopSilenceMs);
+    } catch (error) {
+      console.error("Failed to save auto-stop silence:", error);
+    }
+  }
+
+  async saveTranscriptionLanguage() {
+    try {
+      await invoke("save_transcription_language", {
+        language: this.transcriptionLanguage,
+        translate: this.translateToEnglish,
+      });
+      console.log("Saved transcription language:", this.transcriptionLanguage, "translate:", this.translateToEnglish);
+    } catch (error) {
+      console.error("Failed to save transcription language:", error);
+    }
+  }
+
+  async saveWhisperPrompts() {
+    try {
+      await invoke("save_whisper_prompts", {
+        prompt: this.whisperPrompt,
+        presetPrompts: this.presetPrompts,
+      });
+      console.log("Saved Whisper prompts:", this.whisperPrompt, this.presetPrompts);
+    } catch (error) {
+      console.error("Failed to save Whisper prompts:", error);
+    }
+  }
+
+  async saveDecodingSettings() {
+    try {
+      await invoke("save_decoding_settings", { decoding: this.decoding });
+      console.log("Saved decoding settings:", this.decoding);
+    } catch (error) {
+      console.error("Failed to save decoding settings:", error);
+    }
+  }
+
+  async saveOutputFilterSettings() {
+    try {
+      await invoke("save_output_filter_settings", { filter: this.outputFilter });
+      console.log("Saved output filter settings:", this.outputFilter);
+    } catch (error) {
+      console.error("Failed to save output filter settings:", error);
+    }
+  }
+
+  async saveLiveTranscriptionSettings() {
+    try {
+      await invoke("save_live_transcription_settings", { live: this.liveTranscription });
+      console.log("Saved live transcription settings:", this.liveTranscription);
+    } catch (error) {
+      console.error("Failed to save live transcription settings:", error);
+    }
+  }
+
+  async saveGpuSettings() {
+    try {
+      await invoke("save_gpu_settings", { useGpu: this.useGpu, gpuDevice: this.gpuDevice });
+      console.log("Saved GPU settings:", this.useGpu, this.gpuDevice);
+    } catch (error) {
+      console.error("Failed to save GPU settings:", error);
+    }
+  }
+
+  // GPU settings changed since the model was loaded
+  get gpuSettingsChanged(): boolean {
+    return this.loadedGpuSettings !== null && this.loadedGpuSettings !== `${this.useGpu}:${this.gpuDevice}`;
+  }
+
+  async loadWhisperBackend() {
+    try {
+      this.whisperBackend = await invoke("get_whisper_backend");
+    } catch (error) {
+      console.error("Failed to load Whisper backend:", error);
+    }
+  }
+
+  async loadInputDevices() {
+    try {
+      this.inputDevices = await invoke("get_input_devices");
+    } catch (error) {
+      console.error("Failed to load input devices:", error);
+    }
+  }
+
+  async saveInputDevice() {
+    try {
+      await invoke("save_input_device", {
+        deviceName: this.selectedInputDevice || null,
+      });
+      console.log("Saved input device:", this.selectedInputDevice || "(default)");
+    } catch (error) {
+      console.error("Failed to save input device:", error);
+    }
+  }
+
+  async saveReconnectOnDeviceLost() {
+    try {
+      await invoke("save_reconnect_on_device_lost", { enabled: this.reconnectOnDeviceLost });
+      console.log("Saved reconnect on device lost:", this.reconnectOnDeviceLost);
+    } catch (error) {
+      console.error("Failed to save reconnect on device lost:", error);
+    }
+  }
+
+  // Channel count of the selected (or default) input device
+  get inputDeviceChannels(): number {
+    const device = this.inputDevices.find((d) =>
+      this.selectedInputDevice ? d.name === this.selectedInputDevice : d.is_default
+    );
+    const channels = device?.supported_configs.map((c) => c.channels) ?? [];
+    return channels.length > 0 ? Math.max(...channels) : 2;
+  }
+
+  async saveChannelMix() {
+    try {
+      await invoke("save_channel_mix", { channelMix: this.channelMix });
+      console.log("Saved channel mix:", this.channelMix);
+    } catch (error) {
+      console.error("Failed to save channel mix:", error);
+    }
+  }
+
+  async saveArchiveSettings() {
+    try {
+      await invoke("save_archive_settings", {
+        archiveRecordings: this.archiveRecordings,
+        archiveSource: this.archiveSource,
+      });
+      console.log("Saved archive settings:", this.archiveRecordings, this.archiveSource);
+    } catch (error) {
+      console.error("Failed to save archive settings:", error);
+    }
+  }
+
+  async saveResampleQuality() {
+    try {
+      await invoke("save_resample_quality", { resampleQuality: this.resampleQuality });
+      console.log("Saved resample quality:", this.resampleQuality);
+    } catch (error) {
+      console.error("Failed to save resample quality:", error);
+    }
+  }
+
+  async saveVadSettings() {
+    const vad: VadSettings = {
+      enabled: this.vadEnabled,
+      engine: this.vadEngine,
+      threshold: this.vadThreshold,
+      padding_chunks: this.vadPaddingChunks,
+      keep_original_ratio: this.vadKeepOriginalRatio,
+      segment_gap_ms: this.vadSegmentGapMs,
+    };
+    try {
+      await invoke("save_vad_settings", { vad });
+      console.log("Saved VAD settings:", vad);
+    } catch (error) {
+      console.error("Failed to save VAD settings:", error);
+    }
+  }
+
+  async saveHandsFreeSettings() {
+    const handsFree: HandsFreeSettings = {
+      enabled: this.handsFreeEnabled,
+      silence_timeout_ms: this.silenceTimeoutMs,
+      min_speech_ms: this.minSpeechMs,
+    };
+    try {
+      await invoke("save_hands_free_settings", { handsFree });
+      console.log("Saved hands-free settings:", handsFree);
+    } catch (error) {
+      console.error("Failed to save hands-free settings:", error);
+      this.errorMessage = `ハンズフリーモードを開始できませんでした: ${error}`;
+    }
+  }
+
+  async saveWarmCaptureSettings() {
+    try {
+      await invoke("save_warm_capture_settings", {
+        alwaysWarm: this.alwaysWarm,
+        prerollMs: this.prerollMs,
+      });
+      console.log("Saved warm capture settings:", this.alwaysWarm, this.prerollMs);
+    } catch (error) {
+      console.error("Failed to save warm capture settings:", error);
+    }
+  }
+
   async saveOutputMode() {
     try {
       await invoke("save_output_mode", { mode: this.outputMode });
@@ -224,9 +530,11 @@ class SettingsStore {
       await invoke("initialize_whisper", { modelName: this.selectedModel });
       this.isModelInitialized = true;
       this.currentLoadedModel = this.selectedModel;
+      this.loadedGpuSettings = `${this.useGpu}:${this.gpuDevice}`;
       this.isDownloading = false;
       this.downloadProgress = null;
       this.statusMessage = `準備完了 - ${this.shortcutKey} で録音開始/停止`;
+      await this.loadWhisperBackend();
 
       // Save the selected model for next time
       await this.saveModelSelection(this.selectedModel);
@@ -245,11 +553,49 @@ class SettingsStore {
       const result = await invoke("toggle_recording");
       console.log(result);
     } catch (error) {
+      // Reported by the transcription-cancelled event
+      if (error === TRANSCRIPTION_CANCELLED) return;
       this.errorMessage = `録音エラー: ${error}`;
       console.error(error);
     }
   }
 
+  async cancelTranscription() {
+    try {
+      await invoke("cancel_transcription");
+    } catch (error) {
+      console.warn("Failed to cancel transcription:", error);
+    }
+  }
+
+  async loadSupportedAudioExtensions() {
+    try {
+      this.supportedAudioExtensions = await invoke("get_supported_audio_extensions");
+    } catch (error) {
+      console.error("Failed to load supported audio extensions:", error);
+    }
+  }
+
+  async transcribeFile(path: string) {
+    const fileName = path.split(/[\\/]/).pop() || path;
+    try {
+      this.errorMessage = "";
+      this.fileTranscription = { file_name: fileName, stage: "decoding", progress: 0 };
+      this.statusMessage = `${fileName} を文字起こし中...`;
+      const result: string = await invoke("transcribe_file", { path });
+      console.log("File transcription complete:", result);
+    } catch (error) {
+      this.isTranscribing = false;
+      this.isLlmRefining = false;
+      if (error !== TRANSCRIPTION_CANCELLED) {
+        this.errorMessage = `ファイル文字起こしエラー: ${error}`;
+      }
+      console.error(error);
+    } finally {
+      this.fileTranscription = null;
+    }
+  }
+
   async loadPresetPrompts() {
     try {
       const presets: [string, string][] = await invoke("get_preset_prompts");
@@ -296,12 +642,17 @@ class SettingsStore {
     return (async () => {
       try {
         this.shortcutError = "";
-        await invoke("save_shortcut_setting", { shortcut: this.pendingShortcut });
-        this.shortcutKey = this.pendingShortcut;
+        if (this.shortcutEditTarget === "cancel") {
+          await invoke("save_cancel_shortcut", { shortcut: this.pendingShortcut });
+          this.cancelShortcutKey = this.pendingShortcut;
+        } else {
+          await invoke("save_shortcut_setting", { shortcut: this.pendingShortcut });
+          this.shortcutKey = this.pendingShortcut;
+        }
         this.isEditingShortcut = false;
         this.pendingShortcut = "";
         this.shortcutChanged = true;
-        console.log("Saved shortcut:", this.shortcutKey);
+        console.log("Saved shortcut:", this.shortcutEditTarget, this.shortcutKey, this.cancelShortcutKey);
       } catch (error) {
         this.shortcutError = String(error);
         console.error("Failed to save shortcut:", error);
@@ -309,13 +660,25 @@ class SettingsStore {
     })();
   }
 
+  async clearCancelShortcut() {
+    try {
+      this.shortcutError = "";
+      await invoke("save_cancel_shortcut", { shortcut: "" });
+      this.cancelShortcutKey = "";
+    } catch (error) {
+      this.shortcutError = String(error);
+      console.error("Failed to clear cancel shortcut:", error);
+    }
+  }
+
   cancelShortcutEdit() {
     this.shortcutError = "";
     this.pendingShortcut = "";
     this.isEditingShortcut = false;
   }
 
-  startShortcutEdit() {
+  startShortcutEdit(target: ShortcutTarget = "recording_toggle") {
+    this.shortcutEditTarget = target;
     this.pendingShortcut = "";
     this.shortcutError = "";
     this.shortcutChanged = false;
@@ -34,11 +34,131 @@ export interface ModelInfo {
   size_hint: string;
 }
 
+// Audio level types
+export interface AudioLevel {
+  rms: number;
+  peak: number;
+  rms_db: number;
+  peak_db: number;
+  clipping: boolean;
+  silent: boolean;
+}
+
+export type LevelWarning = 'Silence' | 'Clipping';
+
+export type AutoStopReason = 'MaxLength' | 'Silence';
+
 // Settings types
 export interface WhisperSettings {
   model_name: string;
   insert_newline: boolean;
   max_recording_seconds: number;
+  auto_stop_silence_ms: number;
+  language: string; // language code or "auto"
+  translate: boolean; // translate to English
+  prompt: WhisperPrompt;
+  preset_prompts: Partial<Record<PromptPreset, WhisperPrompt>>; // overrides per prompt preset
+  decoding: DecodingSettings;
+  use_gpu: boolean; // only effective in GPU builds
+  gpu_device: number;
+  live: LiveTranscriptionSettings;
+  filter: OutputFilterSettings;
+}
+
+// Removal of Whisper hallucinations and runaway repetition
+export interface OutputFilterSettings {
+  enabled: boolean;
+  blocklist: string[]; // phrases removed as whole segments, trailing "*" matches any continuation
+  max_repeats: number; // 0 = no repetition filter
+  no_speech_prob: number; // 1.0 = keep every segment
+}
+
+export type FilterReason = 'Blocklist' | 'Repetition' | 'NoSpeech';
+
+export interface FilterRemoval {
+  reason: FilterReason;
+  text: string;
+  start_secs: number;
+}
+
+export const filterReasonLabels: Record<FilterReason, string> = {
+  Blocklist: '除外リスト',
+  Repetition: '繰り返し',
+  NoSpeech: '無音',
+};
+
+// Partial results while recording
+export interface LiveTranscriptionSettings {
+  enabled: boolean;
+  interval_ms: number; // time between transcriptions
+  window_secs: number; // window length before it slides past confirmed text
+}
+
+export const defaultLiveTranscriptionSettings: LiveTranscriptionSettings = {
+  enabled: false,
+  interval_ms: 1000,
+  window_secs: 15,
+};
+
+// Live transcription text, emitted as "transcription-partial"
+export interface PartialTranscript {
+  stable: string; // confirmed, only grows
+  unstable: string; // may still change
+}
+
+// Transcription jobs run one at a time on a worker, emitted as "transcription-job"
+export type JobStatus = 'Queued' | 'Running' | 'Completed' | 'Failed' | 'Cancelled';
+
+export interface TranscriptionJobEvent {
+  id: number;
+  status: JobStatus;
+  pending: number; // jobs queued or running after this change
+}
+
+// Compute backend of whisper.cpp (GPU backends are cargo features)
+export type WhisperBackend = 'Cpu' | 'OpenBlas' | 'Cuda' | 'Vulkan';
+
+export interface WhisperBackendInfo {
+  compiled: WhisperBackend; // backend of this build
+  active: WhisperBackend | null; // backend of the loaded model
+}
+
+export const whisperBackendLabels: Record<WhisperBackend, string> = {
+  Cpu: 'CPU',
+  OpenBlas: 'CPU (OpenBLAS)',
+  Cuda: 'GPU (CUDA)',
+  Vulkan: 'GPU (Vulkan)',
+};
+
+// Whisper decoder parameters
+export interface DecodingSettings {
+  beam_size: number; // 0 or 1 = greedy
+  best_of: number; // candidates per temperature (greedy)
+  temperature: number;
+  temperature_inc: number; // 0 = no temperature fallback
+  no_speech_thold: number;
+  logprob_thold: number;
+  n_threads: number; // 0 = default
+  suppress_blank: boolean;
+  suppress_non_speech: boolean;
+}
+
+export const defaultDecodingSettings: DecodingSettings = {
+  beam_size: 0,
+  best_of: 1,
+  temperature: 0.0,
+  temperature_inc: 0.2,
+  no_speech_thold: 0.6,
+  logprob_thold: -1.0,
+  n_threads: 0,
+  suppress_blank: true,
+  suppress_non_speech: false,
+};
+
+// Initial prompt biasing Whisper towards product names and jargon
+export interface WhisperPrompt {
+  initial_prompt: string;
+  vocabulary: string[];
 }
 
 export type PromptPreset = 'Default' | 'Meeting' | 'Memo' | 'Chat' | 'Custom';
@@ -68,16 +188,83 @@ export const llmProviderDefaultUrls: Record<LlmProvider, string> = {
 
 export interface ShortcutSettings {
   recording_toggle: string;
+  /** Cancels the transcription in progress ("" = no shortcut) */
+  cancel: string;
+}
+
+/** Error of a transcription stopped by `cancel_transcription` */
+export const TRANSCRIPTION_CANCELLED = 'Transcription cancelled';
+
+/** Shortcut being edited in the shortcut settings */
+export type ShortcutTarget = 'recording_toggle' | 'cancel';
+
+export interface AudioSettings {
+  input_device: string | null;
+  reconnect_on_device_lost: boolean;
+  always_warm: boolean;
+  preroll_ms: number;
+  channel_mix: ChannelMix;
+  archive_recordings: boolean;
+  archive_source: ArchiveSource;
+  resample_quality: ResampleQuality;
 }
 
+// Speech detector (Silero falls back to Energy when the model cannot be loaded)
+export type VadEngine = 'Silero' | 'Energy';
+
+export interface VadSettings {
+  enabled: boolean;
+  engine: VadEngine;
+  threshold: number; // speech probability 0.0-1.0
+  padding_chunks: number; // 32ms chunks kept around speech
+  keep_original_ratio: number; // speech fraction above which the original audio is used
+  segment_gap_ms: number; // silence inserted between speech segments
+}
+
+// Voice-activated recording
+export interface HandsFreeSettings {
+  enabled: boolean;
+  silence_timeout_ms: number; // silence that ends an utterance
+  min_speech_ms: number; // shorter utterances are ignored
+}
+
+// Resampling quality preset (speed/quality trade-off)
+export type ResampleQuality = 'Fast' | 'Balanced' | 'Best';
+
+// Audio written to the recording archive
+export type ArchiveSource = 'Captured' | 'Resampled';
+
+// Mono downmix of a multi-channel input device (channel is 0-based)
+export type ChannelMix =
+  | { type: 'Average' }
+  | { type: 'Single'; channel: number }
+  | { type: 'Custom'; gains: number[] };
+
 export interface Settings {
   whisper: WhisperSettings;
   llm: LlmSettings;
   output_mode: OutputMode;
   shortcut: ShortcutSettings;
+  audio: AudioSettings;
+  vad: VadSettings;
+  hands_free: HandsFreeSettings;
   is_saved: boolean;
 }
 
+// Audio device types
+export interface SupportedConfigInfo {
+  channels: number;
+  min_sample_rate: number;
+  max_sample_rate: number;
+  sample_format: string;
+}
+
+export interface InputDeviceInfo {
+  name: string;
+  is_default: boolean;
+  supported_configs: SupportedConfigInfo[];
+}
+
 // Log types
 export interface LogEntry {
   id: string;
@@ -87,6 +274,42 @@ export interface LogEntry {
   audio_duration_secs: number | null;
   llm_used: boolean;
   prompt_preset: string | null;
+  audio_path?: string | null;
+  source_file?: string | null;
+  language?: string | null; // configured or detected spoken language
+  translated?: boolean; // translated to English by Whisper
+  segments?: TranscriptSegment[];
+  filtered?: FilterRemoval[]; // text removed by the hallucination filter
+}
+
+// Whisper segment, times in seconds from the start of the recording
+export interface TranscriptSegment {
+  start_secs: number;
+  end_secs: number;
+  text: string;
+  avg_token_prob: number; // 0.0-1.0
+  no_speech_prob: number; // 0.0-1.0
+}
+
+export interface TranscriptionResult {
+  text: string;
+  segments: TranscriptSegment[];
+  language: string | null;
+}
+
+// Progress of an audio file transcription
+export type FileTranscriptionStage =
+  | 'decoding'
+  | 'resampling'
+  | 'vad'
+  | 'transcribing'
+  | 'refining'
+  | 'complete';
+
+export interface FileTranscriptionProgress {
+  file_name: string;
+  stage: FileTranscriptionStage;
+  progress: number;
 }
 
 // Description types